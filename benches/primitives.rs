@@ -0,0 +1,114 @@
+/*
+    Benchmarks of every setup primitive across a range of k.
+
+    Save a baseline for each build:
+    cargo bench --bench primitives -- --save-baseline serial
+    cargo bench --bench primitives --features=parallel -- --save-baseline parallel
+
+    Compare the current tree against a saved baseline:
+    cargo bench --bench primitives -- --baseline serial
+*/
+use ark_bn254::{Bn254, Fr, G1Projective, G2Projective};
+use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use mock_cq_setup::{
+    compute_g_powers, compute_qs, compute_tau_powers, gen_table, lagrange_commitments,
+    lagrange_openings_commitments_at_zero, UpperToeplitz,
+};
+
+const KS: [usize; 3] = [8, 10, 12];
+
+fn tau() -> Fr {
+    Fr::from(100u64)
+}
+
+fn bench_tau_powers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("compute_tau_powers");
+    for k in KS {
+        group.bench_with_input(BenchmarkId::from_parameter(k), &k, |b, &k| {
+            b.iter(|| compute_tau_powers(tau(), 1 << k))
+        });
+    }
+    group.finish();
+}
+
+fn bench_g_powers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("compute_g_powers");
+    group.sample_size(10);
+    for k in KS {
+        group.bench_with_input(BenchmarkId::new("G1", k), &k, |b, &k| {
+            b.iter(|| compute_g_powers::<G1Projective>(tau(), 1 << k))
+        });
+        group.bench_with_input(BenchmarkId::new("G2", k), &k, |b, &k| {
+            b.iter(|| compute_g_powers::<G2Projective>(tau(), (1 << k) + 1))
+        });
+    }
+    group.finish();
+}
+
+fn bench_lagrange(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lagrange");
+    group.sample_size(10);
+    for k in KS {
+        group.bench_with_input(BenchmarkId::new("commitments", k), &k, |b, &k| {
+            b.iter(|| lagrange_commitments::<G1Projective>(tau(), 1 << k))
+        });
+        group.bench_with_input(BenchmarkId::new("openings_at_zero", k), &k, |b, &k| {
+            b.iter(|| lagrange_openings_commitments_at_zero::<G1Projective>(tau(), 1 << k))
+        });
+    }
+    group.finish();
+}
+
+fn bench_qs(c: &mut Criterion) {
+    let mut group = c.benchmark_group("compute_qs");
+    group.sample_size(10);
+    for k in KS {
+        let n = 1 << k;
+        let t = gen_table::<Fr>(k, Some("bench")).unwrap();
+        let domain = GeneralEvaluationDomain::<Fr>::new(n).unwrap();
+        let tau_powers = compute_tau_powers(tau(), n);
+        group.bench_with_input(BenchmarkId::from_parameter(k), &k, |b, _| {
+            b.iter(|| compute_qs::<Bn254>(&t, &domain, &tau_powers))
+        });
+    }
+    group.finish();
+}
+
+fn bench_toeplitz(c: &mut Criterion) {
+    let mut group = c.benchmark_group("UpperToeplitz::mul_by_vec");
+    for k in KS {
+        let n = 1 << k;
+        let t = gen_table::<Fr>(k, Some("bench")).unwrap();
+        let toeplitz = UpperToeplitz::from_poly(&t);
+        let x = compute_tau_powers(tau(), n);
+        group.bench_with_input(BenchmarkId::from_parameter(k), &k, |b, _| {
+            b.iter(|| toeplitz.mul_by_vec(&x))
+        });
+    }
+    group.finish();
+}
+
+fn bench_gen_table(c: &mut Criterion) {
+    let mut group = c.benchmark_group("gen_table");
+    for k in KS {
+        group.bench_with_input(BenchmarkId::new("seeded", k), &k, |b, &k| {
+            b.iter(|| gen_table::<Fr>(k, Some("bench")))
+        });
+        group.bench_with_input(BenchmarkId::new("range", k), &k, |b, &k| {
+            b.iter(|| gen_table::<Fr>(k, None))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_tau_powers,
+    bench_g_powers,
+    bench_lagrange,
+    bench_qs,
+    bench_toeplitz,
+    bench_gen_table
+);
+criterion_main!(benches);
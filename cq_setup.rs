@@ -0,0 +1,1058 @@
+use ark_bls12_381::Bls12_381;
+use ark_bn254::{Bn254, G1Affine, G2Affine};
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use mock_cq_setup::{
+    abi_encode, circuit_key_dir, circuit_keys, diff_artifacts, import_table, parse_table_values,
+    serialize_vec, setup, setup_file_from_json, setup_file_to_json, solidity_verifier, union_qs,
+    verify_lagrange_commitments, verify_openings_at_zero, verify_powers, verify_qs, verify_vk,
+    write_bytes, Artifact, ArtifactHeader, Elements, Encoding, EvmEncode, EvmError, G2Order,
+    JsonFormat, NumberFormat, Overflow, Padding, ResourceUsage, Section, SetupError, SetupFile,
+    SetupFileError, SetupHeader, SetupReport, TableFile, TableFingerprint, TableForm, TableFormat,
+    TableSpec, TableUnion, VerifierKey, SETUP_MAGIC,
+};
+use num_bigint::BigUint;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::process;
+use std::time::Instant;
+
+/// Exit code of errors, clap exits with 2 on usage errors
+const EXIT_ERROR: i32 = 1;
+/// Exit code of `verify` when a section fails its check or belongs to another table
+const EXIT_INCONSISTENT: i32 = 3;
+/// Exit code of `diff` when the files differ
+const EXIT_DIFFERENT: i32 = 4;
+
+/// Mock setup for the cq lookup argument
+#[derive(Parser)]
+#[command(name = "cq-setup", version)]
+struct Cli {
+    /// Curve of the tables and the setup
+    #[arg(long, value_enum, default_value_t = Curve::Bn254, global = true)]
+    curve: Curve,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Curve {
+    Bn254,
+    #[value(name = "bls12-381")]
+    Bls12_381,
+}
+
+impl Curve {
+    /// Name written into file headers
+    fn name(&self) -> &'static str {
+        match self {
+            Curve::Bn254 => "bn254",
+            Curve::Bls12_381 => "bls12-381",
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generates a standard table, imports one from a file, or merges several into one
+    GenTable(GenTableArgs),
+    /// Runs the setup for a table and writes every artifact
+    Setup(SetupArgs),
+    /// Writes the circuit Lagrange commitments and x_b0_bound for a range of circuit sizes
+    CircuitKeys(CircuitKeysArgs),
+    /// Checks the setup artifacts against each other and against the table
+    Verify(VerifyArgs),
+    /// Prints the header, size, hash and first and last elements of table and setup files
+    Inspect(InspectArgs),
+    /// Compares two table or setup files, or two setup directories section by section
+    Diff(DiffArgs),
+    /// Converts a table file between evaluation and coefficient form
+    Convert(ConvertArgs),
+    /// Derives the verifier key from the G2 powers and the table
+    ExportVk(ExportVkArgs),
+    /// Writes a Solidity contract that verifies cq proofs with the verifier key, bn254 only
+    ExportSolidity(ExportSolidityArgs),
+    /// Writes a bn254 setup file as json or as ABI encoded calldata for EVM and JavaScript tools
+    Encode(EncodeArgs),
+    /// Reads a json document written by encode back into a setup file
+    Decode(DecodeArgs),
+}
+
+#[derive(Args)]
+struct GenTableArgs {
+    /// `seeded:{k}:{seed}`, `sequence:{k}`, `range:{bits}`, `xor`, `and`, `or`,
+    /// `spread:{bits}[:{base}]`, `byte-to-bits`, or a .csv, .json or one value per line file.
+    /// Several sources are merged into one table with the tag of each source folded in.
+    #[arg(required = true)]
+    sources: Vec<String>,
+    /// Output table file, merged tables also get `{out}.json`
+    #[arg(long)]
+    out: String,
+    /// Padding of imported tables: zero, repeat-last or exact
+    #[arg(long, value_parser = parse_padding, default_value = "repeat-last")]
+    padding: Padding,
+    /// Imported values outside the field: reject or reduce
+    #[arg(long, value_parser = parse_overflow, default_value = "reject")]
+    overflow: Overflow,
+    /// Also writes the qs of merged tables to `{out}.qs`, decimal or 0x hex
+    #[arg(long, value_parser = parse_biguint)]
+    tau: Option<BigUint>,
+}
+
+/// Where the setup artifacts are, each one is `{dir}/{section}.bin` unless given explicitly
+#[derive(Args)]
+struct ArtifactPaths {
+    #[arg(long, default_value = "setup")]
+    dir: String,
+    #[arg(long)]
+    g1_powers: Option<String>,
+    #[arg(long)]
+    g2_powers: Option<String>,
+    #[arg(long)]
+    lagrange_commitments: Option<String>,
+    #[arg(long)]
+    circuit_lagrange_commitments: Option<String>,
+    #[arg(long)]
+    lagrange_openings_at_zero: Option<String>,
+    #[arg(long)]
+    qs: Option<String>,
+    #[arg(long)]
+    vk: Option<String>,
+    #[arg(long)]
+    x_b0_bound: Option<String>,
+}
+
+impl ArtifactPaths {
+    fn path(&self, section: Section) -> String {
+        let path = match section {
+            Section::G1Powers => &self.g1_powers,
+            Section::G2Powers => &self.g2_powers,
+            Section::LagrangeCommitments => &self.lagrange_commitments,
+            Section::CircuitLagrangeCommitments => &self.circuit_lagrange_commitments,
+            Section::LagrangeOpeningsAtZero => &self.lagrange_openings_at_zero,
+            Section::Qs => &self.qs,
+            Section::VerifierKey => &self.vk,
+            Section::XB0Bound => &self.x_b0_bound,
+        };
+        path.clone().unwrap_or_else(|| {
+            Path::new(&self.dir)
+                .join(format!("{}.bin", section.name()))
+                .to_string_lossy()
+                .into_owned()
+        })
+    }
+}
+
+#[derive(Args)]
+struct SetupArgs {
+    /// Table file written by gen-table, its size is the table size N
+    #[arg(long)]
+    table: String,
+    /// Toxic waste, decimal or 0x hex
+    #[arg(long, value_parser = parse_biguint)]
+    tau: BigUint,
+    /// log2 of the circuit size n, defaults to log2 N
+    #[arg(long)]
+    circuit_k: Option<usize>,
+    #[command(flatten)]
+    paths: ArtifactPaths,
+    /// Writes the time and memory of every stage as JSON
+    #[arg(long)]
+    report: Option<String>,
+    /// compressed, or uncompressed to memory-map the artifacts
+    #[arg(long, value_parser = parse_encoding, default_value = "compressed")]
+    encoding: Encoding,
+}
+
+#[derive(Args)]
+struct CircuitKeysArgs {
+    /// Toxic waste, decimal or 0x hex
+    #[arg(long, value_parser = parse_biguint)]
+    tau: BigUint,
+    /// log2 of the SRS size, the largest circuit size it serves
+    #[arg(long)]
+    srs_k: usize,
+    /// log2 of the circuit sizes as m_min..m_max, inclusive
+    #[arg(long, value_parser = parse_range)]
+    ms: RangeInclusive<usize>,
+    /// The key of circuits of size 2^m is written to `{dir}/circuit_{m}`
+    #[arg(long, default_value = "setup")]
+    dir: String,
+    /// compressed, or uncompressed to memory-map the artifacts
+    #[arg(long, value_parser = parse_encoding, default_value = "compressed")]
+    encoding: Encoding,
+}
+
+#[derive(Args)]
+struct VerifyArgs {
+    /// Table file the setup was run for
+    #[arg(long)]
+    table: String,
+    #[command(flatten)]
+    paths: ArtifactPaths,
+}
+
+#[derive(Args)]
+struct InspectArgs {
+    /// Table or setup files, or setup directories
+    #[arg(required = true)]
+    files: Vec<String>,
+    /// Number of elements printed at the start and at the end of every file
+    #[arg(long, default_value_t = 3)]
+    points: usize,
+}
+
+#[derive(Args)]
+struct DiffArgs {
+    a: String,
+    b: String,
+}
+
+#[derive(Args)]
+struct ConvertArgs {
+    /// Table or setup file, legacy tables without a header are accepted
+    input: String,
+    #[arg(long)]
+    out: String,
+    /// evaluations or coefficients, for tables
+    #[arg(long, value_parser = parse_form)]
+    form: Option<TableForm>,
+    /// compressed or uncompressed, for setup files
+    #[arg(long, value_parser = parse_encoding)]
+    encoding: Option<Encoding>,
+}
+
+/// Output of `encode`
+#[derive(Clone, Copy)]
+enum ExternalFormat {
+    Json,
+    /// `0x` prefixed hex
+    Abi,
+}
+
+#[derive(Args)]
+struct EncodeArgs {
+    /// Setup file, its section decides between G1 points, G2 points and verifier keys
+    input: String,
+    #[arg(long)]
+    out: String,
+    /// json or abi
+    #[arg(long, value_parser = parse_external_format, default_value = "json")]
+    format: ExternalFormat,
+    /// decimal or hex, for json
+    #[arg(long, value_parser = parse_numbers, default_value = "decimal")]
+    numbers: NumberFormat,
+    /// evm (x.c1, x.c0, y.c1, y.c0) or arkworks (x.c0, x.c1, y.c0, y.c1)
+    #[arg(long, value_parser = parse_g2_order, default_value = "evm")]
+    g2_order: G2Order,
+    /// Encodes only the element at this index, e.g. a single qs entry
+    #[arg(long)]
+    index: Option<usize>,
+}
+
+#[derive(Args)]
+struct DecodeArgs {
+    /// Json document written by encode
+    input: String,
+    #[arg(long)]
+    out: String,
+    /// compressed or uncompressed
+    #[arg(long, value_parser = parse_encoding, default_value = "compressed")]
+    encoding: Encoding,
+}
+
+#[derive(Args)]
+struct ExportVkArgs {
+    /// Table file the setup was run for
+    #[arg(long)]
+    table: String,
+    /// log2 of the circuit size n, defaults to log2 N
+    #[arg(long)]
+    circuit_k: Option<usize>,
+    /// Reads the G2 powers and writes the key to `--vk`
+    #[command(flatten)]
+    paths: ArtifactPaths,
+}
+
+#[derive(Args)]
+struct ExportSolidityArgs {
+    /// Table file the setup was run for
+    #[arg(long)]
+    table: String,
+    /// Reads the key written by export-vk from `--vk`
+    #[command(flatten)]
+    paths: ArtifactPaths,
+    #[arg(long, default_value = "CqVerifier.sol")]
+    out: String,
+}
+
+fn parse_padding(s: &str) -> Result<Padding, String> {
+    match s {
+        "zero" => Ok(Padding::Zero),
+        "repeat-last" => Ok(Padding::RepeatLast),
+        "exact" => Ok(Padding::Exact),
+        _ => Err("expected zero, repeat-last or exact".to_string()),
+    }
+}
+
+fn parse_overflow(s: &str) -> Result<Overflow, String> {
+    match s {
+        "reject" => Ok(Overflow::Reject),
+        "reduce" => Ok(Overflow::Reduce),
+        _ => Err("expected reject or reduce".to_string()),
+    }
+}
+
+fn parse_form(s: &str) -> Result<TableForm, String> {
+    match s {
+        "evaluations" => Ok(TableForm::Evaluations),
+        "coefficients" => Ok(TableForm::Coefficients),
+        _ => Err("expected evaluations or coefficients".to_string()),
+    }
+}
+
+fn parse_encoding(s: &str) -> Result<Encoding, String> {
+    match s {
+        "compressed" => Ok(Encoding::Compressed),
+        "uncompressed" => Ok(Encoding::Uncompressed),
+        _ => Err("expected compressed or uncompressed".to_string()),
+    }
+}
+
+fn parse_external_format(s: &str) -> Result<ExternalFormat, String> {
+    match s {
+        "json" => Ok(ExternalFormat::Json),
+        "abi" => Ok(ExternalFormat::Abi),
+        _ => Err("expected json or abi".to_string()),
+    }
+}
+
+fn parse_numbers(s: &str) -> Result<NumberFormat, String> {
+    let names = NumberFormat::ALL.map(|numbers| numbers.name());
+    NumberFormat::from_name(s).ok_or_else(|| format!("expected {}", names.join(" or ")))
+}
+
+fn parse_g2_order(s: &str) -> Result<G2Order, String> {
+    let names = G2Order::ALL.map(|order| order.name());
+    G2Order::from_name(s).ok_or_else(|| format!("expected {}", names.join(" or ")))
+}
+
+/// `{min}..{max}`, both inclusive
+fn parse_range(s: &str) -> Result<RangeInclusive<usize>, String> {
+    let expected = || "expected m_min..m_max".to_string();
+    let (min, max) = s.split_once("..").ok_or_else(expected)?;
+    Ok(min.parse().map_err(|_| expected())?..=max.parse().map_err(|_| expected())?)
+}
+
+/// Decimal or `0x` prefixed hex integer of any size
+fn parse_biguint(s: &str) -> Result<BigUint, String> {
+    let (radix, digits) = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(digits) => (16, digits),
+        None => (10, s),
+    };
+    BigUint::parse_bytes(digits.as_bytes(), radix)
+        .ok_or_else(|| format!("`{}` is not a decimal or 0x hex integer", s))
+}
+
+/// What went wrong, and in which file
+enum Failure {
+    Setup {
+        path: Option<String>,
+        err: SetupError,
+    },
+    Message(String),
+    /// `diff` found differences, they are already printed
+    Different,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::Setup {
+                path: Some(path),
+                err,
+            } => write!(f, "{}: {}", path, err),
+            Failure::Setup { path: None, err } => write!(f, "{}", err),
+            Failure::Message(message) => write!(f, "{}", message),
+            Failure::Different => write!(f, "files differ"),
+        }
+    }
+}
+
+impl From<SetupError> for Failure {
+    fn from(err: SetupError) -> Self {
+        Failure::Setup { path: None, err }
+    }
+}
+
+/// Attaches the path to errors of reading or writing it
+fn at<E: Into<SetupError>>(path: &str) -> impl FnOnce(E) -> Failure + '_ {
+    move |err| Failure::Setup {
+        path: Some(path.to_string()),
+        err: err.into(),
+    }
+}
+
+fn to_field<F: PrimeField>(name: &str, x: &BigUint) -> Result<F, Failure> {
+    if *x >= F::MODULUS.into() {
+        return Err(Failure::Message(format!(
+            "{} is not smaller than the scalar field modulus",
+            name
+        )));
+    }
+    Ok(F::from(x.clone()))
+}
+
+fn gen_table<E: Pairing>(curve: &str, args: &GenTableArgs) -> Result<(), Failure> {
+    let is_file = |source: &str| Path::new(source).is_file();
+    let parse_spec = |source: &str| source.parse::<TableSpec>().map_err(Failure::Message);
+
+    if let [source] = &args.sources[..] {
+        let (k, t, padding, spec) = if is_file(source) {
+            let format = TableFormat::from_path(source);
+            let (k, t) =
+                import_table::<E::ScalarField>(source, format, args.overflow, args.padding)
+                    .map_err(at(source))?;
+            (k, t, Some(args.padding), format!("import:{}", source))
+        } else {
+            let spec = parse_spec(source)?;
+            (spec.k(), spec.table()?, None, spec.to_string())
+        };
+        let file = TableFile::from_poly(curve, k, padding, &spec, &t).map_err(at(&args.out))?;
+        write_bytes(&args.out, &file.to_bytes()).map_err(at(&args.out))?;
+        println!("Wrote {} table of size 2^{} to {}.", spec, k, args.out);
+        return Ok(());
+    }
+
+    let mut tables = vec![];
+    for source in args.sources.iter() {
+        let evals = if is_file(source) {
+            let contents = std::fs::read_to_string(source).map_err(at(source))?;
+            let format = TableFormat::from_path(source);
+            parse_table_values::<E::ScalarField>(&contents, format, args.overflow)
+                .map_err(at(source))?
+        } else {
+            parse_spec(source)?.evaluations()?
+        };
+        tables.push((source.clone(), evals));
+    }
+    let union = TableUnion::new(&tables)?;
+
+    let spec = format!("union:{}", args.sources.join(","));
+    let file = TableFile::new(
+        curve,
+        TableForm::Evaluations,
+        None,
+        &spec,
+        union.evaluations(),
+    )
+    .map_err(at(&args.out))?;
+    write_bytes(&args.out, &file.to_bytes()).map_err(at(&args.out))?;
+    let json_path = format!("{}.json", args.out);
+    write_bytes(&json_path, union.metadata_json().as_bytes()).map_err(at(&json_path))?;
+
+    if let Some(tau) = &args.tau {
+        let qs = union_qs::<E>(&union, to_field("tau", tau)?)?;
+        let qs_path = format!("{}.qs", args.out);
+        write_bytes(&qs_path, &serialize_vec(&qs)).map_err(at(&qs_path))?;
+    }
+
+    println!(
+        "Merged {} tables into a table of size 2^{} in {}.",
+        union.entries.len(),
+        union.k,
+        args.out
+    );
+    Ok(())
+}
+
+fn read_table<E: Pairing>(curve: &str, path: &str) -> Result<TableFile<E::ScalarField>, Failure> {
+    TableFile::read(path, curve).map_err(at(path))
+}
+
+/// Reads a section and rejects it unless it was computed for `table`
+fn read_section<T: CanonicalSerialize + CanonicalDeserialize + Send>(
+    curve: &str,
+    paths: &ArtifactPaths,
+    section: Section,
+    table: &TableFingerprint,
+) -> Result<Vec<T>, Failure> {
+    let path = paths.path(section);
+    let file = SetupFile::read_for_table(&path, curve, section, table).map_err(at(&path))?;
+    Ok(file.data)
+}
+
+fn write_section<T: CanonicalSerialize + CanonicalDeserialize + Send>(
+    curve: &str,
+    paths: &ArtifactPaths,
+    section: Section,
+    encoding: Encoding,
+    table: TableFingerprint,
+    data: Vec<T>,
+) -> Result<(), Failure> {
+    let path = paths.path(section);
+    if let Some(dir) = Path::new(&path).parent() {
+        std::fs::create_dir_all(dir).map_err(at(&path))?;
+    }
+    SetupFile::with_encoding(curve, section, encoding, data)
+        .for_table(table)
+        .write(&path)
+        .map_err(at(&path))?;
+    println!("Wrote {} to {}.", section, path);
+    Ok(())
+}
+
+fn run_setup<E: Pairing>(curve: &str, args: &SetupArgs) -> Result<(), Failure> {
+    let table = read_table::<E>(curve, &args.table)?;
+    let table_k = table.header.k as usize;
+    let circuit_k = args.circuit_k.unwrap_or(table_k);
+    let tau = to_field::<E::ScalarField>("tau", &args.tau)?;
+    let t = table.table().map_err(at(&args.table))?;
+    let fingerprint = table.fingerprint().map_err(at(&args.table))?;
+
+    let usage_before = ResourceUsage::current();
+    let now = Instant::now();
+    let output = setup::<E>(tau, 1 << table_k, 1 << circuit_k, &t)?;
+    let elapsed_time = now.elapsed();
+    for timing in output.timings.iter() {
+        println!(
+            "Running {}() took {:.3} seconds.",
+            timing.stage,
+            timing.wall_time.as_secs_f64()
+        );
+    }
+    println!(
+        "Running setup took {:.3} seconds.",
+        elapsed_time.as_secs_f64()
+    );
+
+    let (paths, encoding) = (&args.paths, args.encoding);
+    for (section, data) in [
+        (Section::G1Powers, output.g1_powers),
+        (Section::LagrangeCommitments, output.lagrange_commitments),
+        (
+            Section::CircuitLagrangeCommitments,
+            output.circuit_lagrange_commitments,
+        ),
+        (
+            Section::LagrangeOpeningsAtZero,
+            output.lagrange_openings_at_zero,
+        ),
+        (Section::Qs, output.qs),
+    ] {
+        write_section(curve, paths, section, encoding, fingerprint, data)?;
+    }
+    write_section(
+        curve,
+        paths,
+        Section::G2Powers,
+        encoding,
+        fingerprint,
+        output.g2_powers,
+    )?;
+
+    if let Some(report_path) = &args.report {
+        let report = SetupReport::new(
+            curve,
+            table_k,
+            circuit_k,
+            elapsed_time,
+            usage_before,
+            output.timings,
+        );
+        write_bytes(report_path, report.to_json().as_bytes()).map_err(at(report_path))?;
+    }
+    Ok(())
+}
+
+fn run_circuit_keys<E: Pairing>(curve: &str, args: &CircuitKeysArgs) -> Result<(), Failure> {
+    let tau = to_field::<E::ScalarField>("tau", &args.tau)?;
+    let now = Instant::now();
+    let keys = circuit_keys::<E>(tau, args.srs_k, args.ms.clone())?;
+    println!(
+        "Running circuit_keys() took {:.3} seconds.",
+        now.elapsed().as_secs_f64()
+    );
+
+    for (&m, key) in keys.iter() {
+        let dir = circuit_key_dir(Path::new(&args.dir), m);
+        key.write(curve, &dir, args.encoding)
+            .map_err(at(&dir.to_string_lossy()))?;
+        println!("Wrote the key for m = {} to {}.", m, dir.display());
+    }
+    Ok(())
+}
+
+fn verify<E: Pairing>(curve: &str, args: &VerifyArgs) -> Result<(), Failure> {
+    let table = read_table::<E>(curve, &args.table)?;
+    let t = table.table().map_err(at(&args.table))?;
+    let fingerprint = table.fingerprint().map_err(at(&args.table))?;
+    let paths = &args.paths;
+    let g1_powers = read_section::<E::G1Affine>(curve, paths, Section::G1Powers, &fingerprint)?;
+    let g2_powers = read_section::<E::G2Affine>(curve, paths, Section::G2Powers, &fingerprint)?;
+
+    let ok = |section: Section| println!("{} ok", section);
+    verify_powers::<E>(&g1_powers, &g2_powers)?;
+    ok(Section::G1Powers);
+    ok(Section::G2Powers);
+
+    for section in [
+        Section::LagrangeCommitments,
+        Section::CircuitLagrangeCommitments,
+    ] {
+        let lagrange = read_section::<E::G1Affine>(curve, paths, section, &fingerprint)?;
+        verify_lagrange_commitments::<E>(section, &g1_powers, &lagrange)?;
+        ok(section);
+    }
+
+    let openings =
+        read_section::<E::G1Affine>(curve, paths, Section::LagrangeOpeningsAtZero, &fingerprint)?;
+    verify_openings_at_zero::<E>(&g1_powers, &openings)?;
+    ok(Section::LagrangeOpeningsAtZero);
+
+    let qs = read_section::<E::G1Affine>(curve, paths, Section::Qs, &fingerprint)?;
+    verify_qs::<E>(&g1_powers, &g2_powers, &t, &qs)?;
+    ok(Section::Qs);
+
+    // the verifier key is exported separately, so it is only checked when present
+    if Path::new(&paths.path(Section::VerifierKey)).exists() {
+        let vks = read_section::<VerifierKey<E>>(curve, paths, Section::VerifierKey, &fingerprint)?;
+        for vk in vks {
+            vk.expect_table(&fingerprint)?;
+            verify_vk(&vk, &g2_powers, &t)?;
+        }
+        ok(Section::VerifierKey);
+    }
+    Ok(())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Files of every section in a setup directory, or the path itself
+fn expand(path: &str) -> Vec<String> {
+    if !Path::new(path).is_dir() {
+        return vec![path.to_string()];
+    }
+    Section::ALL
+        .iter()
+        .map(|section| {
+            Path::new(path)
+                .join(format!("{}.bin", section.name()))
+                .to_string_lossy()
+                .into_owned()
+        })
+        .filter(|path| Path::new(path).is_file())
+        .collect()
+}
+
+fn format_element<E: Pairing>(
+    header: &ArtifactHeader,
+    elements: &Elements,
+    i: usize,
+) -> Result<String, SetupError> {
+    Ok(match header {
+        ArtifactHeader::Setup(header) => match header.section {
+            Section::G2Powers | Section::XB0Bound => elements.get::<E::G2Affine>(i)?.to_string(),
+            Section::VerifierKey => format!("{:?}", elements.get::<VerifierKey<E>>(i)?),
+            Section::G1Powers
+            | Section::LagrangeCommitments
+            | Section::CircuitLagrangeCommitments
+            | Section::LagrangeOpeningsAtZero
+            | Section::Qs => elements.get::<E::G1Affine>(i)?.to_string(),
+        },
+        ArtifactHeader::Table(_) | ArtifactHeader::LegacyTable => {
+            elements.get::<E::ScalarField>(i)?.to_string()
+        }
+    })
+}
+
+/// Element `i` as a point or field element of `curve`, or as hex for unknown curves
+fn element_string(curve: &str, artifact: &Artifact, i: usize) -> Result<String, SetupError> {
+    let (header, elements) = (&artifact.header, &artifact.elements);
+    match curve {
+        "bn254" => format_element::<Bn254>(header, elements, i),
+        "bls12-381" => format_element::<Bls12_381>(header, elements, i),
+        _ => Ok(hex(elements.bytes(i))),
+    }
+}
+
+/// Curve of the file, legacy tables have none and are read as `default`
+fn artifact_curve<'a>(artifact: &'a Artifact, default: &'a str) -> &'a str {
+    match &artifact.header {
+        ArtifactHeader::Table(header) => &header.curve,
+        ArtifactHeader::Setup(header) => &header.curve,
+        ArtifactHeader::LegacyTable => default,
+    }
+}
+
+fn inspect(curve: &str, args: &InspectArgs) -> Result<(), Failure> {
+    for path in args.files.iter().flat_map(|path| expand(path)) {
+        let bytes = std::fs::read(&path).map_err(at(&path))?;
+        let artifact = Artifact::parse(&bytes).map_err(at(&path))?;
+        let elements = &artifact.elements;
+        let hash = match &artifact.header {
+            ArtifactHeader::Setup(header) => {
+                println!("{}: setup file, {} bytes", path, bytes.len());
+                println!("  curve    {}", header.curve);
+                println!("  section  {}", header.section);
+                match &header.table {
+                    Some(fingerprint) => println!("  table    {}", fingerprint),
+                    None => println!("  table    none"),
+                }
+                header.hash
+            }
+            ArtifactHeader::Table(header) => {
+                println!("{}: table file, {} bytes", path, bytes.len());
+                println!("  curve    {}", header.curve);
+                println!("  k        {}", header.k);
+                println!("  form     {:?}", header.form);
+                println!("  padding  {:?}", header.padding);
+                println!("  spec     {}", header.spec);
+                header.hash
+            }
+            ArtifactHeader::LegacyTable => {
+                println!("{}: legacy table file, {} bytes", path, bytes.len());
+                println!("  curve    {} (from --curve)", curve);
+                artifact.content_hash
+            }
+        };
+        println!(
+            "  len      {} elements of {} bytes",
+            elements.len(),
+            elements.stride()
+        );
+        if hash == artifact.content_hash {
+            println!("  hash     {}", hex(&hash));
+        } else {
+            println!("  hash     {} does not match the content", hex(&hash));
+        }
+
+        let curve = artifact_curve(&artifact, curve);
+        let n = elements.len();
+        for i in (0..n).filter(|i| *i < args.points || *i + args.points >= n) {
+            if i > args.points && i + args.points == n {
+                println!("  ...");
+            }
+            let element = element_string(curve, &artifact, i).map_err(at(&path))?;
+            println!("  [{}] {}", i, element);
+        }
+    }
+    Ok(())
+}
+
+/// Compares two files, returns whether they are equal
+fn diff_files(curve: &str, a_path: &str, b_path: &str) -> Result<bool, Failure> {
+    let a_bytes = std::fs::read(a_path).map_err(at(a_path))?;
+    let b_bytes = std::fs::read(b_path).map_err(at(b_path))?;
+    let a = Artifact::parse(&a_bytes).map_err(at(a_path))?;
+    // setup files in different encodings are compared point by point in the encoding of a
+    let encodings = match (&a.header, SetupHeader::parse(&b_bytes)) {
+        (ArtifactHeader::Setup(a), Ok((b, _))) if a.encoding != b.encoding => Some(a.encoding),
+        _ => None,
+    };
+    let b_bytes = match encodings {
+        Some(encoding) => reencode_setup_file(&b_bytes, encoding).map_err(at(b_path))?,
+        None => b_bytes,
+    };
+    let b = Artifact::parse(&b_bytes).map_err(at(b_path))?;
+    let mut diff = diff_artifacts(&a, &b);
+    if encodings.is_some() {
+        diff.fields.push("encoding");
+    }
+    if diff.is_equal() {
+        println!("{} and {} are identical", a_path, b_path);
+        return Ok(true);
+    }
+
+    println!("{} and {} differ", a_path, b_path);
+    if !diff.fields.is_empty() {
+        println!("  header   {}", diff.fields.join(", "));
+    }
+    if diff.lens.0 != diff.lens.1 {
+        println!("  len      {} vs {}", diff.lens.0, diff.lens.1);
+    }
+    if let Some(i) = diff.first_difference {
+        println!("  first differing element at index {}", i);
+        for (path, artifact) in [(a_path, &a), (b_path, &b)] {
+            if i < artifact.elements.len() {
+                let element = element_string(artifact_curve(artifact, curve), artifact, i)
+                    .map_err(at(path))?;
+                println!("  {} [{}] {}", path, i, element);
+            }
+        }
+    }
+    Ok(false)
+}
+
+fn diff(curve: &str, args: &DiffArgs) -> Result<(), Failure> {
+    let (a, b) = (Path::new(&args.a), Path::new(&args.b));
+    let equal = if a.is_dir() && b.is_dir() {
+        let mut equal = true;
+        for section in Section::ALL {
+            let name = format!("{}.bin", section.name());
+            let (a_path, b_path) = (a.join(&name), b.join(&name));
+            match (a_path.is_file(), b_path.is_file()) {
+                (true, true) => {
+                    let (a_path, b_path) = (a_path.to_string_lossy(), b_path.to_string_lossy());
+                    equal &= diff_files(curve, &a_path, &b_path)?;
+                }
+                (true, false) => {
+                    println!("{} only in {}", name, args.a);
+                    equal = false;
+                }
+                (false, true) => {
+                    println!("{} only in {}", name, args.b);
+                    equal = false;
+                }
+                (false, false) => {}
+            }
+        }
+        equal
+    } else {
+        diff_files(curve, &args.a, &args.b)?
+    };
+
+    if equal {
+        Ok(())
+    } else {
+        Err(Failure::Different)
+    }
+}
+
+fn reencode<T: CanonicalSerialize + CanonicalDeserialize + Send>(
+    header: &SetupHeader,
+    bytes: &[u8],
+    encoding: Encoding,
+) -> Result<Vec<u8>, SetupFileError> {
+    let (curve, section) = (&header.curve, header.section);
+    let file = SetupFile::<T>::from_bytes(bytes, curve, section)?;
+    Ok(file.into_encoding(encoding).to_bytes())
+}
+
+fn reencode_section<E: Pairing>(
+    header: &SetupHeader,
+    bytes: &[u8],
+    encoding: Encoding,
+) -> Result<Vec<u8>, SetupFileError> {
+    match header.section {
+        Section::G2Powers | Section::XB0Bound => reencode::<E::G2Affine>(header, bytes, encoding),
+        Section::VerifierKey => reencode::<VerifierKey<E>>(header, bytes, encoding),
+        Section::G1Powers
+        | Section::LagrangeCommitments
+        | Section::CircuitLagrangeCommitments
+        | Section::LagrangeOpeningsAtZero
+        | Section::Qs => reencode::<E::G1Affine>(header, bytes, encoding),
+    }
+}
+
+/// The same setup file in `encoding`, decoded on the curve named in its header
+fn reencode_setup_file(bytes: &[u8], encoding: Encoding) -> Result<Vec<u8>, SetupFileError> {
+    let (header, _) = SetupHeader::parse(bytes)?;
+    match header.curve.as_str() {
+        "bn254" => reencode_section::<Bn254>(&header, bytes, encoding),
+        "bls12-381" => reencode_section::<Bls12_381>(&header, bytes, encoding),
+        _ => Err(SetupFileError::CurveMismatch {
+            expected: "bn254 or bls12-381".to_string(),
+            found: header.curve,
+        }),
+    }
+}
+
+fn convert<E: Pairing>(curve: &str, args: &ConvertArgs) -> Result<(), Failure> {
+    let bytes = std::fs::read(&args.input).map_err(at(&args.input))?;
+    if bytes.starts_with(&SETUP_MAGIC) {
+        let encoding = args.encoding.ok_or_else(|| {
+            Failure::Message("converting a setup file needs --encoding".to_string())
+        })?;
+        let converted = reencode_setup_file(&bytes, encoding).map_err(at(&args.input))?;
+        write_bytes(&args.out, &converted).map_err(at(&args.out))?;
+        println!("Wrote {:?} {} to {}.", encoding, args.input, args.out);
+        return Ok(());
+    }
+
+    let form = args
+        .form
+        .ok_or_else(|| Failure::Message("converting a table needs --form".to_string()))?;
+    let file = TableFile::<E::ScalarField>::from_bytes(&bytes, curve)
+        .map_err(at(&args.input))?
+        .to_form(form)
+        .map_err(at(&args.input))?;
+    write_bytes(&args.out, &file.to_bytes()).map_err(at(&args.out))?;
+    println!("Wrote {:?} of {} to {}.", form, file.header.spec, args.out);
+    Ok(())
+}
+
+fn export_vk<E: Pairing>(curve: &str, args: &ExportVkArgs) -> Result<(), Failure> {
+    let table = read_table::<E>(curve, &args.table)?;
+    let table_k = table.header.k as usize;
+    let circuit_k = args.circuit_k.unwrap_or(table_k);
+    let t = table.table().map_err(at(&args.table))?;
+    let fingerprint = table.fingerprint().map_err(at(&args.table))?;
+    let g2_powers =
+        read_section::<E::G2Affine>(curve, &args.paths, Section::G2Powers, &fingerprint)?;
+
+    let vk = VerifierKey::<E>::from_srs(&g2_powers, &t, 1 << table_k, 1 << circuit_k, fingerprint)?;
+    write_section(
+        curve,
+        &args.paths,
+        Section::VerifierKey,
+        Encoding::Compressed,
+        fingerprint,
+        vec![vk],
+    )
+}
+
+fn export_solidity(curve: &str, args: &ExportSolidityArgs) -> Result<(), Failure> {
+    if curve != "bn254" {
+        return Err(Failure::Message(
+            "the EVM precompiles only support bn254".to_string(),
+        ));
+    }
+    let table = read_table::<Bn254>(curve, &args.table)?;
+    let fingerprint = table.fingerprint().map_err(at(&args.table))?;
+    let vks =
+        read_section::<VerifierKey<Bn254>>(curve, &args.paths, Section::VerifierKey, &fingerprint)?;
+    let vk_path = args.paths.path(Section::VerifierKey);
+    let [vk] = vks.as_slice() else {
+        return Err(Failure::Message(format!(
+            "{}: expected one verifier key, found {}",
+            vk_path,
+            vks.len()
+        )));
+    };
+    vk.expect_table(&fingerprint).map_err(at(&vk_path))?;
+
+    let contract = solidity_verifier(vk).map_err(at(&vk_path))?;
+    write_bytes(&args.out, contract.as_bytes()).map_err(at(&args.out))?;
+    println!("Wrote the verifier for {} to {}.", fingerprint, args.out);
+    Ok(())
+}
+
+fn encode_section<T: EvmEncode + CanonicalSerialize + CanonicalDeserialize + Send>(
+    header: &SetupHeader,
+    bytes: &[u8],
+    args: &EncodeArgs,
+) -> Result<String, SetupError> {
+    let file = SetupFile::<T>::from_bytes(bytes, &header.curve, header.section)?;
+    let format = JsonFormat {
+        numbers: args.numbers,
+        g2_order: args.g2_order,
+    };
+    let element = |i: usize| {
+        file.data.get(i).ok_or(SetupError::IndexOutOfRange {
+            index: i,
+            len: file.data.len(),
+        })
+    };
+    let json = |value: serde_json::Value| serde_json::to_string_pretty(&value).unwrap();
+    Ok(match (args.format, args.index) {
+        (ExternalFormat::Json, None) => json(setup_file_to_json(&file, format)),
+        (ExternalFormat::Json, Some(i)) => json(element(i)?.to_json(format)),
+        (ExternalFormat::Abi, None) => format!("0x{}", hex(&abi_encode(&file.data, args.g2_order))),
+        (ExternalFormat::Abi, Some(i)) => format!("0x{}", hex(&element(i)?.to_abi(args.g2_order))),
+    })
+}
+
+fn encode(args: &EncodeArgs) -> Result<(), Failure> {
+    let bytes = std::fs::read(&args.input).map_err(at(&args.input))?;
+    let (header, _) = SetupHeader::parse(&bytes).map_err(at(&args.input))?;
+    if header.curve != "bn254" {
+        return Err(at(&args.input)(EvmError::UnsupportedCurve(header.curve)));
+    }
+    let encoded = match header.section {
+        Section::G2Powers | Section::XB0Bound => encode_section::<G2Affine>(&header, &bytes, args),
+        Section::VerifierKey => encode_section::<VerifierKey<Bn254>>(&header, &bytes, args),
+        Section::G1Powers
+        | Section::LagrangeCommitments
+        | Section::CircuitLagrangeCommitments
+        | Section::LagrangeOpeningsAtZero
+        | Section::Qs => encode_section::<G1Affine>(&header, &bytes, args),
+    }
+    .map_err(at(&args.input))?;
+    write_bytes(&args.out, format!("{}\n", encoded).as_bytes()).map_err(at(&args.out))?;
+    println!("Wrote {} to {}.", args.input, args.out);
+    Ok(())
+}
+
+fn decode_section<T: EvmEncode + CanonicalSerialize + CanonicalDeserialize + Send>(
+    document: &serde_json::Value,
+    encoding: Encoding,
+) -> Result<Vec<u8>, EvmError> {
+    let file = setup_file_from_json::<T>(document)?;
+    Ok(file.into_encoding(encoding).to_bytes())
+}
+
+fn decode(args: &DecodeArgs) -> Result<(), Failure> {
+    let contents = std::fs::read_to_string(&args.input).map_err(at(&args.input))?;
+    let document: serde_json::Value = serde_json::from_str(&contents)
+        .map_err(EvmError::from)
+        .map_err(at(&args.input))?;
+    let section = document["section"]
+        .as_str()
+        .and_then(Section::from_name)
+        .ok_or_else(|| EvmError::Malformed {
+            field: "section".to_string(),
+        })
+        .map_err(at(&args.input))?;
+    let bytes = match section {
+        Section::G2Powers | Section::XB0Bound => {
+            decode_section::<G2Affine>(&document, args.encoding)
+        }
+        Section::VerifierKey => decode_section::<VerifierKey<Bn254>>(&document, args.encoding),
+        Section::G1Powers
+        | Section::LagrangeCommitments
+        | Section::CircuitLagrangeCommitments
+        | Section::LagrangeOpeningsAtZero
+        | Section::Qs => decode_section::<G1Affine>(&document, args.encoding),
+    }
+    .map_err(at(&args.input))?;
+    write_bytes(&args.out, &bytes).map_err(at(&args.out))?;
+    println!("Wrote {} to {}.", args.input, args.out);
+    Ok(())
+}
+
+fn run<E: Pairing>(curve: &str, command: &Command) -> Result<(), Failure> {
+    match command {
+        Command::GenTable(args) => gen_table::<E>(curve, args),
+        Command::Setup(args) => run_setup::<E>(curve, args),
+        Command::CircuitKeys(args) => run_circuit_keys::<E>(curve, args),
+        Command::Verify(args) => verify::<E>(curve, args),
+        Command::Inspect(args) => inspect(curve, args),
+        Command::Diff(args) => diff(curve, args),
+        Command::Convert(args) => convert::<E>(curve, args),
+        Command::ExportVk(args) => export_vk::<E>(curve, args),
+        Command::ExportSolidity(args) => export_solidity(curve, args),
+        Command::Encode(args) => encode(args),
+        Command::Decode(args) => decode(args),
+    }
+}
+
+// cargo run --release --features=parallel --bin cq-setup -- --help
+fn main() {
+    let cli = Cli::parse();
+    let curve = cli.curve.name();
+    let result = match cli.curve {
+        Curve::Bn254 => run::<Bn254>(curve, &cli.command),
+        Curve::Bls12_381 => run::<Bls12_381>(curve, &cli.command),
+    };
+
+    if let Err(failure) = result {
+        if matches!(failure, Failure::Different) {
+            process::exit(EXIT_DIFFERENT);
+        }
+        eprintln!("error: {}", failure);
+        let code = match failure {
+            Failure::Setup {
+                err: SetupError::Inconsistent { .. } | SetupError::TableMismatch { .. },
+                ..
+            } => EXIT_INCONSISTENT,
+            _ => EXIT_ERROR,
+        };
+        process::exit(code);
+    }
+}
@@ -1,97 +1,374 @@
 use ark_ec::pairing::Pairing;
 use ark_ff::FftField;
-use ark_poly::{
-    univariate::DensePolynomial, DenseUVPolynomial, EvaluationDomain, GeneralEvaluationDomain,
-};
+use ark_poly::univariate::DensePolynomial;
 use mock_cq_setup::{
-    compute_g_powers, compute_qs, compute_tau_powers, deserialize_vec, lagrange_commitments,
-    lagrange_openings_commitments_at_zero,
+    available_memory_bytes, circuit_key_dir, circuit_keys, estimate_setup, gen_table,
+    reports_to_csv, reports_to_json, setup, write_bytes, Calibration, Encoding, ResourceUsage,
+    SetupError, SetupReport, TableFile, LEGACY_SPEC,
 };
 use std::env;
+use std::ops::RangeInclusive;
+use std::path::Path;
+use std::process;
 use std::time::Instant;
 
-// Given N, runs the setup
-fn run<E: Pairing>(n: usize, tau: E::ScalarField, table_path: &str) {
-    let now = Instant::now();
-    let powers_of_tau = compute_tau_powers(tau, n);
-    let elapsed_time = now.elapsed();
-    println!(
-        "Running compute_tau_powers() took {} seconds.",
-        elapsed_time.as_secs()
-    );
+const CURVE: &str = "bn254";
+/// curves `--estimate` predicts for, named as in `cq-setup --curve`
+const ESTIMATE_CURVES: [&str; 2] = ["bn254", "bls12-381"];
 
+// Given N and n, runs the setup
+fn run<E: Pairing>(
+    table_k: usize,
+    circuit_k: usize,
+    tau: E::ScalarField,
+    t: &DensePolynomial<E::ScalarField>,
+) -> Result<SetupReport, SetupError> {
+    let usage_before = ResourceUsage::current();
     let now = Instant::now();
-    let _ = lagrange_commitments::<E::G1>(tau, n as u64);
+    let output = setup::<E>(tau, 1 << table_k, 1 << circuit_k, t)?;
     let elapsed_time = now.elapsed();
-    println!(
-        "Running lagrange_commitments() took {} seconds.",
-        elapsed_time.as_secs()
-    );
 
-    let now = Instant::now();
-    let _ = lagrange_openings_commitments_at_zero::<E::G1>(tau, n);
-    let elapsed_time = now.elapsed();
+    for timing in output.timings.iter() {
+        println!(
+            "Running {}() took {:.3} seconds.",
+            timing.stage,
+            timing.wall_time.as_secs_f64()
+        );
+    }
     println!(
-        "Running lagrange_openings_commitments_at_zero() took {} seconds.",
-        elapsed_time.as_secs()
+        "Running setup took {:.3} seconds.",
+        elapsed_time.as_secs_f64()
     );
 
-    let now = Instant::now();
-    let _ = compute_g_powers::<E::G1>(tau, n);
-    let elapsed_time = now.elapsed();
+    Ok(SetupReport::new(
+        CURVE,
+        table_k,
+        circuit_k,
+        elapsed_time,
+        usage_before,
+        output.timings,
+    ))
+}
+
+// Reads the table and checks that it has 2^k entries
+fn read_table<F: FftField>(
+    path: &str,
+    k: usize,
+    pad_legacy: bool,
+) -> Result<DensePolynomial<F>, SetupError> {
+    let file =
+        TableFile::<F>::read(path, CURVE).map_err(|err| SetupError::in_file(path)(err.into()))?;
+    // legacy files are trimmed, so they may look smaller than they are
+    let legacy_fits = pad_legacy && file.header.spec == LEGACY_SPEC && file.header.k as usize <= k;
+    if file.header.k as usize != k && !legacy_fits {
+        return Err(SetupError::in_file(path)(SetupError::SizeMismatch {
+            expected: 1 << k,
+            found: 1 << file.header.k,
+        }));
+    }
+    file.table().map_err(SetupError::in_file(path))
+}
+
+struct Args {
+    /// single table k with the table read from `table_path`, or a range of k with generated tables
+    ks: RangeInclusive<usize>,
+    /// circuit k, equal to the table k unless given
+    circuit_k: Option<usize>,
+    tau: u64,
+    table_path: Option<String>,
+    report_path: Option<String>,
+    csv_path: Option<String>,
+    /// only predict time and memory of the setup
+    estimate: bool,
+    /// curve of the estimate, `bn254` or `bls12-381`, setups always run on bn254
+    curve: String,
+    /// accept legacy tables without a header that look smaller than 2^k
+    pad_legacy: bool,
+    /// only compute the circuit keys for these m from an SRS of size 2^k
+    circuit_keys: Option<RangeInclusive<usize>>,
+    /// the circuit key of size 2^m is written to `{out_dir}/circuit_{m}`
+    out_dir: String,
+}
+
+fn usage(msg: &str) -> SetupError {
+    SetupError::Usage(msg.to_string())
+}
+
+fn to_u64(arg: &str) -> Result<u64, SetupError> {
+    arg.parse::<u64>()
+        .map_err(|_| usage(&format!("failed to parse `{}` as u64", arg)))
+}
+
+// `{min}..{max}`, both inclusive
+fn to_range(arg: &str, expected: &str) -> Result<RangeInclusive<usize>, SetupError> {
+    let (min, max) = arg.split_once("..").ok_or_else(|| usage(expected))?;
+    Ok(to_u64(min)? as usize..=to_u64(max)? as usize)
+}
+
+fn parse_args() -> Result<Args, SetupError> {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let mut positional = vec![];
+    let mut report_path = None;
+    let mut csv_path = None;
+    let mut sweep = None;
+    let mut estimate = false;
+    let mut curve = None;
+    let mut pad_legacy = false;
+    let mut table_k = None;
+    let mut circuit_k = None;
+    let mut circuit_keys = None;
+    let mut out_dir = "setup".to_string();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .cloned()
+                .ok_or_else(|| usage(&format!("missing value for {}", arg)))
+        };
+        match arg.as_str() {
+            "--report" => report_path = Some(value()?),
+            "--csv" => csv_path = Some(value()?),
+            "--sweep" => sweep = Some(value()?),
+            "--estimate" => estimate = true,
+            "--curve" => curve = Some(value()?),
+            "--pad-legacy" => pad_legacy = true,
+            "--table-k" => table_k = Some(to_u64(&value()?)? as usize),
+            "--circuit-k" => circuit_k = Some(to_u64(&value()?)? as usize),
+            "--circuit-keys" => circuit_keys = Some(value()?),
+            "--out" => out_dir = value()?,
+            _ => positional.push(arg.clone()),
+        }
+    }
+
+    if curve.is_some() && !estimate {
+        return Err(usage("--curve only applies to --estimate"));
+    }
+    let curve = curve.unwrap_or_else(|| CURVE.to_string());
+    if !ESTIMATE_CURVES.contains(&curve.as_str()) {
+        return Err(usage(&format!(
+            "curve must be one of {}",
+            ESTIMATE_CURVES.join(", ")
+        )));
+    }
+
+    // B0 has degree n - 2, so circuits have at least 2 rows
+    if circuit_k == Some(0) {
+        return Err(SetupError::CircuitTooSmall { size: 1 });
+    }
+
+    let circuit_keys = circuit_keys
+        .map(|range| to_range(&range, "circuit sizes must be given as m_min..m_max"))
+        .transpose()?;
+
+    match sweep {
+        Some(range) => {
+            let ks = to_range(&range, "sweep must be given as k_min..k_max")?;
+            if ks.is_empty() {
+                return Err(usage("empty sweep range"));
+            }
+            if !(positional.len() == 1 || estimate && positional.is_empty()) {
+                return Err(usage("sweep expects only {tau}"));
+            }
+            if table_k.is_some() {
+                return Err(usage("sweep is over the table k"));
+            }
+            if circuit_keys.is_some() {
+                return Err(usage("sweep does not compute circuit keys"));
+            }
+
+            Ok(Args {
+                ks,
+                circuit_k,
+                tau: positional.first().map_or(Ok(0), |tau| to_u64(tau))?,
+                table_path: None,
+                report_path,
+                csv_path,
+                estimate,
+                curve,
+                pad_legacy,
+                circuit_keys,
+                out_dir,
+            })
+        }
+        None => {
+            let expected = if circuit_keys.is_some() {
+                "expected {k} {tau}"
+            } else {
+                "expected {k} {tau} {table_path}"
+            };
+            // without --table-k the table k is the first positional argument
+            let k = match table_k {
+                Some(k) => k,
+                None if positional.is_empty() => return Err(usage(expected)),
+                None => to_u64(&positional.remove(0))? as usize,
+            };
+            let fits = match circuit_keys {
+                Some(_) => positional.len() == 1,
+                None => positional.len() == 2 || estimate,
+            };
+            if !fits {
+                return Err(usage(expected));
+            }
+
+            Ok(Args {
+                ks: k..=k,
+                circuit_k,
+                tau: positional.first().map_or(Ok(0), |tau| to_u64(tau))?,
+                table_path: positional.get(1).cloned(),
+                report_path,
+                csv_path,
+                estimate,
+                curve,
+                pad_legacy,
+                circuit_keys,
+                out_dir,
+            })
+        }
+    }
+}
+
+fn mib(bytes: u64) -> f64 {
+    bytes as f64 / (1u64 << 20) as f64
+}
+
+// Prints the predicted time and memory of every stage
+fn print_estimate<E: Pairing>(
+    curve: &str,
+    table_k: usize,
+    circuit_k: usize,
+    calibration: &Calibration,
+) {
+    let estimate = estimate_setup::<E>(table_k, circuit_k, Some(calibration));
     println!(
-        "Running compute_g_powers G1() took {} seconds.",
-        elapsed_time.as_secs()
+        "Estimate for {}, table k = {}, circuit k = {} on {} threads:",
+        curve, table_k, circuit_k, estimate.threads
     );
-
-    let now = Instant::now();
-    let _ = compute_g_powers::<E::G2>(tau, n + 1);
-    let elapsed_time = now.elapsed();
+    for stage in estimate.stages.iter() {
+        println!(
+            "  {}() will take {:.3} seconds and {:.1} MiB.",
+            stage.stage,
+            stage.time.as_secs_f64(),
+            mib(stage.working_memory_bytes)
+        );
+    }
     println!(
-        "Running compute_g_powers G2() took {} seconds.",
-        elapsed_time.as_secs()
+        "  setup will take {:.3} seconds with a peak of {:.1} MiB.",
+        estimate.total_time.as_secs_f64(),
+        mib(estimate.peak_memory_bytes)
     );
+}
+
+// Refuses to start a setup that is predicted to run out of memory
+fn check_memory<E: Pairing>(table_k: usize, circuit_k: usize) -> Result<(), SetupError> {
+    let needed = estimate_setup::<E>(table_k, circuit_k, None).peak_memory_bytes;
+    match available_memory_bytes() {
+        Some(available) if needed > available => {
+            Err(SetupError::InsufficientMemory { needed, available })
+        }
+        _ => Ok(()),
+    }
+}
 
+// Runs only the circuit specific part of the setup for every circuit size 2^m in the range
+// and writes each key to its own directory under `out_dir`
+fn run_circuit_keys<E: Pairing>(
+    srs_k: usize,
+    ms: RangeInclusive<usize>,
+    tau: E::ScalarField,
+    out_dir: &str,
+) -> Result<(), SetupError> {
     let now = Instant::now();
-    let t = read_table::<E::ScalarField>(table_path);
-    let domain = GeneralEvaluationDomain::<E::ScalarField>::new(n).unwrap();
-    let _ = compute_qs::<E>(&t, &domain, &powers_of_tau);
-    let elapsed_time = now.elapsed();
+    let keys = circuit_keys::<E>(tau, srs_k, ms)?;
     println!(
-        "Running compute_qs took {} seconds.",
-        elapsed_time.as_secs()
+        "Running circuit_keys() for m in {}..{} took {:.3} seconds.",
+        keys.keys().next().unwrap(),
+        keys.keys().last().unwrap(),
+        now.elapsed().as_secs_f64()
     );
+    for (&m, key) in keys.iter() {
+        let dir = circuit_key_dir(Path::new(out_dir), m);
+        key.write(CURVE, &dir, Encoding::Compressed)?;
+        println!(
+            "  m = {}: {} lagrange commitments and x_b0_bound written to {}",
+            m,
+            key.lagrange_commitments.len(),
+            dir.display()
+        );
+    }
+    Ok(())
 }
 
-fn read_table<F: FftField>(path: &str) -> DensePolynomial<F> {
-    let table_coeffs = deserialize_vec::<F>(path);
-    DensePolynomial::from_coefficients_vec(table_coeffs)
+use ark_bls12_381::Bls12_381;
+use ark_bn254::{Bn254, Fr};
+// cargo run --features=parallel --bin run_setup {k} {tau} {table_path} [--circuit-k {circuit_k}] [--pad-legacy] [--report report.json] [--csv report.csv]
+// cargo run --features=parallel --bin run_setup --table-k {table_k} --circuit-k {circuit_k} {tau} {table_path}
+// cargo run --features=parallel --bin run_setup --sweep {k_min}..{k_max} {tau} [--circuit-k {circuit_k}] [--report report.json] [--csv report.csv]
+// cargo run --features=parallel --bin run_setup --estimate {k} [--circuit-k {circuit_k}] [--curve bn254|bls12-381]
+// cargo run --features=parallel --bin run_setup --circuit-keys {m_min}..{m_max} {k} {tau} [--out {dir}]
+fn main() {
+    if let Err(err) = try_main() {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
 }
 
-fn parse_args<F: FftField>() -> (u64, F, String) {
-    let args: Vec<String> = env::args().collect();
+// Calibrates on `E` and prints the estimate of every k, then checks the largest one fits
+fn run_estimate<E: Pairing>(args: &Args) -> Result<(), SetupError> {
+    let circuit_k = |table_k: usize| args.circuit_k.unwrap_or(table_k);
+    let largest_k = *args.ks.end();
+    let calibration = Calibration::measure::<E>();
+    for k in args.ks.clone() {
+        print_estimate::<E>(&args.curve, k, circuit_k(k), &calibration);
+    }
+    check_memory::<E>(largest_k, circuit_k(largest_k))
+}
 
-    let to_u64 = |arg: &String| -> u64 {
-        match arg.parse::<u64>() {
-            Ok(arg) => arg,
-            Err(_) => {
-                panic!("Failed to parse argument as u64.");
-            }
-        }
-    };
+fn try_main() -> Result<(), SetupError> {
+    let args = parse_args()?;
+    let tau = Fr::from(args.tau);
+    if let Some(ms) = &args.circuit_keys {
+        return run_circuit_keys::<Bn254>(*args.ks.end(), ms.clone(), tau, &args.out_dir);
+    }
+    let circuit_k = |table_k: usize| args.circuit_k.unwrap_or(table_k);
+    let largest_k = *args.ks.end();
 
-    let k = to_u64(&args[1]);
-    let tau = to_u64(&args[2]);
+    if args.estimate {
+        return match args.curve.as_str() {
+            "bls12-381" => run_estimate::<Bls12_381>(&args),
+            _ => run_estimate::<Bn254>(&args),
+        };
+    }
+    check_memory::<Bn254>(largest_k, circuit_k(largest_k))?;
 
-    let n = 1 << k;
-    let tau = F::from(tau);
+    let reports: Vec<SetupReport> = args
+        .ks
+        .clone()
+        .map(|k| {
+            let t = match &args.table_path {
+                Some(table_path) => read_table::<Fr>(table_path, k, args.pad_legacy)?,
+                None => gen_table::<Fr>(k, None)?,
+            };
+            run::<Bn254>(k, circuit_k(k), tau, &t)
+        })
+        .collect::<Result<_, _>>()?;
 
-    (n, tau, args[3].clone())
-}
+    if let Some(report_path) = &args.report_path {
+        let json = match reports.as_slice() {
+            [report] => report.to_json(),
+            reports => reports_to_json(reports),
+        };
+        write_bytes(report_path, json.as_bytes())?;
+    }
 
-use ark_bn254::{Bn254, Fr};
-// cargo run --bin run_setup {k} {tau} -- --features=parallel
-fn main() {
-    let (n, tau, table_path) = parse_args::<Fr>();
-    run::<Bn254>(n as usize, tau, table_path.as_str());
+    if let Some(csv_path) = &args.csv_path {
+        write_bytes(csv_path, reports_to_csv(&reports).as_bytes())?;
+    }
+
+    if args.ks.start() != args.ks.end() {
+        println!();
+        print!("{}", reports_to_csv(&reports));
+    }
+    Ok(())
 }
@@ -1,32 +1,187 @@
-use ark_bn254::Fr;
-use mock_cq_setup::{gen_table, serialize_vec, write_bytes};
+use ark_bn254::{Bn254, Fr};
+use mock_cq_setup::{
+    import_table, parse_table_values, serialize_vec, union_qs, write_bytes, Overflow, Padding,
+    SetupError, TableFile, TableForm, TableFormat, TableSpec, TableUnion,
+};
 use std::env;
+use std::process;
 
-// cargo run --bin serialize_table {k} {seed} {path} -- --features=parallel
+const CURVE: &str = "bn254";
+
+fn usage(msg: &str) -> SetupError {
+    SetupError::Usage(msg.to_string())
+}
+
+fn missing_value(flag: &str) -> SetupError {
+    usage(&format!("missing value for {}", flag))
+}
+
+// Imports user supplied table values, pads them to the next power of two and interpolates them
+fn import(args: &[String]) -> Result<(), SetupError> {
+    let mut positional = vec![];
+    let mut padding = Padding::RepeatLast;
+    let mut overflow = Overflow::Reject;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .map(String::as_str)
+                .ok_or_else(|| missing_value(arg))
+        };
+        match arg.as_str() {
+            "--padding" => {
+                padding = match value()? {
+                    "zero" => Padding::Zero,
+                    "repeat-last" => Padding::RepeatLast,
+                    "exact" => Padding::Exact,
+                    _ => return Err(usage("padding must be one of zero, repeat-last, exact")),
+                }
+            }
+            "--overflow" => {
+                overflow = match value()? {
+                    "reduce" => Overflow::Reduce,
+                    "reject" => Overflow::Reject,
+                    _ => return Err(usage("overflow must be one of reduce, reject")),
+                }
+            }
+            _ => positional.push(arg.as_str()),
+        }
+    }
+
+    let [values_path, table_path] = positional[..] else {
+        return Err(usage("expected --import {values_path} {table_path}"));
+    };
+
+    let format = TableFormat::from_path(values_path);
+    let (k, table_coeffs) = import_table::<Fr>(values_path, format, overflow, padding)
+        .map_err(SetupError::in_file(values_path))?;
+    let spec = format!("import:{}", values_path);
+    let file = TableFile::from_poly(CURVE, k, Some(padding), &spec, &table_coeffs)?;
+    write_bytes(table_path, &file.to_bytes())?;
+    println!("Imported table of size 2^{} into {}.", k, table_path);
+    Ok(())
+}
+
+// Merges standard tables and imported files into one table with the tags folded in. Writes the
+// union to `{path}`, the offset map to `{path}.json` and, given `--tau`, its qs to `{path}.qs`.
+fn union(args: &[String]) -> Result<(), SetupError> {
+    let mut positional = vec![];
+    let mut tau = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--tau" => {
+                let value = args.next().ok_or_else(|| missing_value(arg))?;
+                tau = Some(to_u64(value)?);
+            }
+            _ => positional.push(arg.as_str()),
+        }
+    }
+
+    let [path, names @ ..] = &positional[..] else {
+        return Err(usage("expected --union {path} {table}..."));
+    };
+    if names.is_empty() {
+        return Err(usage("expected --union {path} {table}..."));
+    }
+
+    let tables: Vec<(String, Vec<Fr>)> = names
+        .iter()
+        .map(|name| {
+            let evals = match name.parse::<TableSpec>() {
+                Ok(spec) => spec.evaluations::<Fr>(),
+                Err(_) => std::fs::read_to_string(name)
+                    .map_err(Into::into)
+                    .and_then(|contents| {
+                        let format = TableFormat::from_path(name);
+                        parse_table_values::<Fr>(&contents, format, Overflow::Reject)
+                    })
+                    .map_err(Into::into),
+            };
+            Ok((name.to_string(), evals.map_err(SetupError::in_file(name))?))
+        })
+        .collect::<Result<_, SetupError>>()?;
+    let union = TableUnion::new(&tables)?;
+
+    let spec = format!("union:{}", names.join(","));
+    let file = TableFile::new(
+        CURVE,
+        TableForm::Evaluations,
+        None,
+        &spec,
+        union.evaluations(),
+    )?;
+    write_bytes(path, &file.to_bytes())?;
+    write_bytes(&format!("{}.json", path), union.metadata_json().as_bytes())?;
+
+    if let Some(tau) = tau {
+        let qs = union_qs::<Bn254>(&union, Fr::from(tau))?;
+        write_bytes(&format!("{}.qs", path), &serialize_vec(&qs))?;
+    }
+
+    println!(
+        "Merged {} tables into a table of size 2^{}.",
+        union.entries.len(),
+        union.k
+    );
+    Ok(())
+}
+
+fn to_u64(arg: &str) -> Result<u64, SetupError> {
+    arg.parse::<u64>()
+        .map_err(|_| usage(&format!("failed to parse `{}` as u64", arg)))
+}
+
+// Writes the table coefficients to `path`, the header carries the spec
+fn write_table(spec: &TableSpec, path: &str) -> Result<(), SetupError> {
+    let table_coeffs = spec.table::<Fr>()?;
+    let file = TableFile::from_poly(CURVE, spec.k(), None, &spec.to_string(), &table_coeffs)?;
+    write_bytes(path, &file.to_bytes())
+}
+
+// cargo run --features=parallel --bin serialize_table {k} {seed} {path}
+// cargo run --features=parallel --bin serialize_table --table {range:B|xor|and|or|spread:B[:BASE]|byte-to-bits} {path}
+// cargo run --features=parallel --bin serialize_table --import {values.csv|values.json|values.txt} {path} [--padding zero|repeat-last|exact] [--overflow reduce|reject]
+// cargo run --features=parallel --bin serialize_table --union {path} {table|values_path}... [--tau {tau}]
 fn main() {
     let args: Vec<String> = env::args().collect();
+    if let Err(err) = run(&args) {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
 
-    let to_u64 = |arg: &String| -> u64 {
-        match arg.parse::<u64>() {
-            Ok(arg) => arg,
-            Err(_) => {
-                panic!("Failed to parse argument as u64.");
-            }
+fn run(args: &[String]) -> Result<(), SetupError> {
+    match args.get(1).map(String::as_str) {
+        Some("--union") => return union(&args[2..]),
+        Some("--import") => return import(&args[2..]),
+        Some("--table") => {
+            let [name, path] = &args[2..] else {
+                return Err(usage("expected --table {name} {path}"));
+            };
+            let spec = name.parse::<TableSpec>().map_err(|err| usage(&err))?;
+            write_table(&spec, path)?;
+            println!("Generated {} table of size 2^{}.", spec, spec.k());
+            return Ok(());
         }
+        _ => {}
+    }
+
+    let [_, k, seed, path] = args else {
+        return Err(usage("expected {k} {seed} {path}"));
     };
+    let k = to_u64(k)? as usize;
 
-    let k = to_u64(&args[1]);
-
-    // {seed} is not equal 0
-    if args[2] != "0" {
-        let table_coeffs = gen_table::<Fr>(k as usize, Some(args[2].as_str()));
-        let data = serialize_vec(&table_coeffs.coeffs);
-        write_bytes(&args[3], &data);
-    } else if args[2] == "0"  {
-        let table_coeffs = gen_table::<Fr>(k as usize, None);
-        let data = serialize_vec(&table_coeffs.coeffs);
-        write_bytes(&args[3], &data);
+    // {seed} equal to 0 gives the sequence [1..1<<k]
+    let spec = if seed != "0" {
+        TableSpec::Seeded {
+            k,
+            seed: seed.clone(),
+        }
     } else {
-        panic!("Incorrect arguments")
+        TableSpec::Sequence { k }
     };
+    write_table(&spec, path)
 }
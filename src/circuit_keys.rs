@@ -0,0 +1,279 @@
+use crate::error::{check_tau, domain, domain_of_log_size, SetupError};
+use crate::serialization::Encoding;
+use crate::setup_file::{Section, SetupFile};
+use crate::utils::is_pow_2;
+use ark_ec::{pairing::Pairing, scalar_mul::fixed_base::FixedBase, AffineRepr, CurveGroup, Group};
+use ark_ff::{batch_inversion, Field, One, PrimeField};
+use ark_poly::EvaluationDomain;
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+/*
+    A single SRS of size 2^K serves circuits of every size 2^m <= 2^K. A circuit of size 2^m
+    needs the Lagrange commitments over its own domain and the degree bound of B0:
+
+                   w_m^i * (tau^(2^m) - 1)
+        L_m,i  =  -------------------------          x_b0_bound_m = [tau^(2^K - 1 - (2^m - 2))]_2
+                     2^m * (tau - w_m^i)
+
+    Every w_m is a power of the root of the largest domain, w_m^i = w^(i * 2^(M - m)), so the
+    inverses of tau - w^j are computed once for the largest m and shared by all smaller ones.
+*/
+
+/// Circuit specific part of the SRS for circuits of size 2^m
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CircuitKey<E: Pairing> {
+    /// [L_i(tau)]_1 over the circuit domain
+    pub lagrange_commitments: Vec<E::G1Affine>,
+    /// [tau^{2^K - 1 - (2^m - 2)}]_2
+    pub x_b0_bound: E::G2Affine,
+}
+
+impl<E: Pairing> CircuitKey<E> {
+    /// Writes the key to `{dir}/circuit_lagrange_commitments.bin` and `{dir}/x_b0_bound.bin`
+    pub fn write(&self, curve: &str, dir: &Path, encoding: Encoding) -> Result<(), SetupError> {
+        std::fs::create_dir_all(dir)?;
+        SetupFile::with_encoding(
+            curve,
+            Section::CircuitLagrangeCommitments,
+            encoding,
+            self.lagrange_commitments.clone(),
+        )
+        .write(&section_path(dir, Section::CircuitLagrangeCommitments))?;
+        SetupFile::with_encoding(curve, Section::XB0Bound, encoding, vec![self.x_b0_bound])
+            .write(&section_path(dir, Section::XB0Bound))
+    }
+
+    /// Reads a key written by `write`
+    pub fn read(curve: &str, dir: &Path) -> Result<Self, SetupError> {
+        let lagrange_commitments = SetupFile::read(
+            &section_path(dir, Section::CircuitLagrangeCommitments),
+            curve,
+            Section::CircuitLagrangeCommitments,
+        )?
+        .data;
+        let x_b0_bound = match SetupFile::read(
+            &section_path(dir, Section::XB0Bound),
+            curve,
+            Section::XB0Bound,
+        )?
+        .data[..]
+        {
+            [x_b0_bound] => x_b0_bound,
+            ref points => {
+                return Err(SetupError::SizeMismatch {
+                    expected: 1,
+                    found: points.len(),
+                })
+            }
+        };
+        Ok(Self {
+            lagrange_commitments,
+            x_b0_bound,
+        })
+    }
+}
+
+/// Directory of the key for circuits of size 2^m, `{dir}/circuit_{m}`
+pub fn circuit_key_dir(dir: &Path, m: usize) -> PathBuf {
+    dir.join(format!("circuit_{}", m))
+}
+
+fn section_path(dir: &Path, section: Section) -> String {
+    dir.join(format!("{}.bin", section.name()))
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn check_range(ms: &RangeInclusive<usize>, srs_n: usize) -> Result<(), SetupError> {
+    if !is_pow_2(srs_n) {
+        return Err(SetupError::NotPow2 { size: srs_n });
+    }
+    if ms.is_empty() {
+        return Err(SetupError::Empty);
+    }
+    // B0 has degree 2^m - 2
+    if *ms.start() < 1 {
+        return Err(SetupError::CircuitTooSmall { size: 1 });
+    }
+    let max_n = 1usize.checked_shl(*ms.end() as u32).unwrap_or(usize::MAX);
+    if max_n > srs_n {
+        return Err(SetupError::SizeMismatch {
+            expected: srs_n,
+            found: max_n,
+        });
+    }
+    Ok(())
+}
+
+fn b0_bound_index(srs_n: usize, m: usize) -> usize {
+    srs_n - 1 - ((1 << m) - 2)
+}
+
+/// Circuit keys for every m in `ms` from tau and an SRS of size 2^srs_k, keyed by m.
+/// All scalar multiplications share one fixed base table per group.
+pub fn circuit_keys<E: Pairing>(
+    tau: E::ScalarField,
+    srs_k: usize,
+    ms: RangeInclusive<usize>,
+) -> Result<BTreeMap<usize, CircuitKey<E>>, SetupError> {
+    let srs_n = domain_of_log_size::<E::ScalarField>(srs_k)?.size();
+    check_range(&ms, srs_n)?;
+
+    let m_max = *ms.end();
+    check_tau(tau, 1 << m_max)?;
+    let domain = domain::<E::ScalarField>(1 << m_max)?;
+    let roots: Vec<E::ScalarField> = domain.elements().collect();
+    let mut tau_minus_roots_inv: Vec<E::ScalarField> = roots.iter().map(|w| tau - w).collect();
+    batch_inversion(&mut tau_minus_roots_inv);
+
+    let mut g1_scalars = vec![];
+    let mut g2_scalars = vec![];
+    for m in ms.clone() {
+        let n = 1u64 << m;
+        let stride = 1 << (m_max - m);
+        let zh = tau.pow([n]) - E::ScalarField::one();
+        let const_multiplier = zh * E::ScalarField::from(n).inverse().unwrap();
+        g1_scalars.extend(
+            (0..n as usize)
+                .map(|i| roots[i * stride] * const_multiplier * tau_minus_roots_inv[i * stride]),
+        );
+        g2_scalars.push(tau.pow([b0_bound_index(srs_n, m) as u64]));
+    }
+
+    let scalar_size = E::ScalarField::MODULUS_BIT_SIZE as usize;
+    let window = FixedBase::get_mul_window_size(g1_scalars.len());
+    let g1_table = FixedBase::get_window_table(scalar_size, window, E::G1::generator());
+    let g1 = FixedBase::msm::<E::G1>(scalar_size, window, &g1_table, &g1_scalars);
+    let g1 = E::G1::normalize_batch(&g1);
+
+    let window = FixedBase::get_mul_window_size(g2_scalars.len());
+    let g2_table = FixedBase::get_window_table(scalar_size, window, E::G2::generator());
+    let g2 = FixedBase::msm::<E::G2>(scalar_size, window, &g2_table, &g2_scalars);
+    let g2 = E::G2::normalize_batch(&g2);
+
+    let mut offset = 0;
+    Ok(ms
+        .zip(g2)
+        .map(|(m, x_b0_bound)| {
+            let n = 1 << m;
+            let lagrange_commitments = g1[offset..offset + n].to_vec();
+            offset += n;
+            (
+                m,
+                CircuitKey {
+                    lagrange_commitments,
+                    x_b0_bound,
+                },
+            )
+        })
+        .collect())
+}
+
+/// Circuit keys for every m in `ms` from the powers of an SRS of size 2^K, without tau.
+/// The Lagrange commitments of size 2^m are the inverse fft of the first 2^m G1 powers.
+pub fn circuit_keys_from_srs<E: Pairing>(
+    g1_powers: &[E::G1Affine],
+    g2_powers: &[E::G2Affine],
+    ms: RangeInclusive<usize>,
+) -> Result<BTreeMap<usize, CircuitKey<E>>, SetupError> {
+    let srs_n = g1_powers.len();
+    check_range(&ms, srs_n)?;
+    if g2_powers.len() < srs_n {
+        return Err(SetupError::SizeMismatch {
+            expected: srs_n,
+            found: g2_powers.len(),
+        });
+    }
+
+    let powers: Vec<E::G1> = g1_powers[..1 << ms.end()]
+        .iter()
+        .map(|p| p.into_group())
+        .collect();
+
+    ms.map(|m| {
+        let n = 1 << m;
+        let domain = domain::<E::ScalarField>(n)?;
+        let lagrange_commitments = E::G1::normalize_batch(&domain.ifft(&powers[..n]));
+        Ok((
+            m,
+            CircuitKey {
+                lagrange_commitments,
+                x_b0_bound: g2_powers[b0_bound_index(srs_n, m)],
+            },
+        ))
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod circuit_keys_test {
+    use super::{circuit_key_dir, circuit_keys, circuit_keys_from_srs, CircuitKey};
+    use crate::{compute_g_powers, lagrange_commitments, Encoding, SetupError};
+    use ark_bn254::{Bn254, Fr, G1Projective, G2Projective};
+    use ark_ff::UniformRand;
+    use ark_std::test_rng;
+
+    // cargo test --features=parallel test_circuit_keys
+    #[test]
+    fn test_circuit_keys() {
+        let srs_k = 5;
+        let srs_n = 1 << srs_k;
+        let tau = Fr::rand(&mut test_rng());
+
+        let keys = circuit_keys::<Bn254>(tau, srs_k, 1..=4).unwrap();
+        assert_eq!(keys.keys().copied().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+
+        let g2_powers = compute_g_powers::<G2Projective>(tau, srs_n + 1);
+        for (&m, key) in keys.iter() {
+            let n = 1 << m;
+            assert_eq!(
+                key.lagrange_commitments,
+                lagrange_commitments::<G1Projective>(tau, n as u64).unwrap()
+            );
+            assert_eq!(key.x_b0_bound, g2_powers[srs_n - 1 - (n - 2)]);
+        }
+
+        let g1_powers = compute_g_powers::<G1Projective>(tau, srs_n);
+        assert_eq!(
+            circuit_keys_from_srs(&g1_powers, &g2_powers, 1..=4).unwrap(),
+            keys
+        );
+
+        assert!(matches!(
+            circuit_keys::<Bn254>(tau, srs_k, 0..=4),
+            Err(SetupError::CircuitTooSmall { size: 1 })
+        ));
+        assert!(matches!(
+            circuit_keys::<Bn254>(tau, srs_k, 1..=6),
+            Err(SetupError::SizeMismatch {
+                expected: 32,
+                found: 64
+            })
+        ));
+    }
+
+    #[test]
+    fn test_write_circuit_keys() {
+        let dir = std::env::temp_dir().join(format!("cq_circuit_keys_test_{}", std::process::id()));
+        let keys = circuit_keys::<Bn254>(Fr::from(100u64), 4, 1..=3).unwrap();
+        for (encoding, (&m, key)) in [Encoding::Compressed, Encoding::Uncompressed]
+            .into_iter()
+            .cycle()
+            .zip(keys.iter())
+        {
+            key.write("bn254", &circuit_key_dir(&dir, m), encoding)
+                .unwrap();
+        }
+        for (&m, key) in keys.iter() {
+            let read = CircuitKey::<Bn254>::read("bn254", &circuit_key_dir(&dir, m)).unwrap();
+            assert_eq!(&read, key);
+        }
+        assert!(matches!(
+            CircuitKey::<Bn254>::read("bn254", &circuit_key_dir(&dir, 4)),
+            Err(SetupError::SetupFile(_))
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
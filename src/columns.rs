@@ -0,0 +1,190 @@
+use crate::common::evaluate_at_tau;
+use crate::compute_qs;
+use crate::error::SetupError;
+use ark_ec::{pairing::Pairing, CurveGroup, Group};
+use ark_ff::{FftField, One, Zero};
+use ark_poly::{univariate::DensePolynomial, GeneralEvaluationDomain};
+use ark_std::{cfg_into_iter, cfg_iter};
+use std::ops::Mul;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/*
+    Tuple lookups use the table T = sum_j gamma^j T_j for a challenge gamma that is only known
+    at proving time. Both qs and [T]_2 are linear in the table, so they are computed once per
+    column and combined with powers of gamma when the challenge is known.
+*/
+
+/// Table with several columns over the same domain
+pub struct MultiColumnTable<F: FftField> {
+    pub columns: Vec<DensePolynomial<F>>,
+}
+
+impl<F: FftField> MultiColumnTable<F> {
+    pub fn new(columns: Vec<DensePolynomial<F>>) -> Result<Self, SetupError> {
+        if columns.is_empty() {
+            return Err(SetupError::Empty);
+        }
+        Ok(Self { columns })
+    }
+
+    /// Interpolates every column from its evaluations over `domain`
+    pub fn from_evaluations(
+        domain: &GeneralEvaluationDomain<F>,
+        columns: &[Vec<F>],
+    ) -> Result<Self, SetupError> {
+        Self::new(
+            columns
+                .iter()
+                .map(|evals| crate::interpolate_table(domain, evals))
+                .collect::<Result<_, _>>()?,
+        )
+    }
+
+    /// T = sum_j gamma^j T_j
+    pub fn combine(&self, gamma: F) -> DensePolynomial<F> {
+        let mut gamma_j = F::one();
+        let mut combined = DensePolynomial::<F> { coeffs: vec![] };
+        for column in self.columns.iter() {
+            combined += (gamma_j, column);
+            gamma_j *= gamma;
+        }
+        combined
+    }
+}
+
+/// Cached quotients and G2 commitments of every column
+pub struct MultiColumnQs<E: Pairing> {
+    /// qs of column j
+    pub qs: Vec<Vec<E::G1Affine>>,
+    /// [T_j(tau)]_2
+    pub t_2: Vec<E::G2Affine>,
+}
+
+impl<E: Pairing> MultiColumnQs<E> {
+    pub fn compute(
+        table: &MultiColumnTable<E::ScalarField>,
+        domain: &GeneralEvaluationDomain<E::ScalarField>,
+        tau_powers: &[E::ScalarField],
+    ) -> Result<Self, SetupError> {
+        let g2 = E::G2::generator();
+        let qs = table
+            .columns
+            .iter()
+            .map(|column| compute_qs::<E>(column, domain, tau_powers))
+            .collect::<Result<_, _>>()?;
+        let t_2 = table
+            .columns
+            .iter()
+            .map(|column| g2.mul(evaluate_at_tau(&column.coeffs, tau_powers)))
+            .collect::<Vec<_>>();
+
+        Ok(Self {
+            qs,
+            t_2: E::G2::normalize_batch(&t_2),
+        })
+    }
+
+    /// qs and [T(tau)]_2 of T = sum_j gamma^j T_j
+    pub fn combine(
+        &self,
+        gamma: E::ScalarField,
+    ) -> Result<(Vec<E::G1Affine>, E::G2Affine), SetupError> {
+        let n = self.qs.first().ok_or(SetupError::Empty)?.len();
+        if let Some(column_qs) = self.qs.iter().find(|column_qs| column_qs.len() != n) {
+            return Err(SetupError::SizeMismatch {
+                expected: n,
+                found: column_qs.len(),
+            });
+        }
+
+        let gamma_powers: Vec<E::ScalarField> =
+            std::iter::successors(Some(E::ScalarField::one()), |g| Some(*g * gamma))
+                .take(self.qs.len())
+                .collect();
+
+        let qs: Vec<E::G1> = cfg_into_iter!(0..n)
+            .map(|i| {
+                self.qs
+                    .iter()
+                    .zip(gamma_powers.iter())
+                    .fold(E::G1::zero(), |acc, (column_qs, gamma_j)| {
+                        acc + column_qs[i].mul(gamma_j)
+                    })
+            })
+            .collect();
+
+        let t_2: E::G2 = cfg_iter!(self.t_2)
+            .zip(cfg_iter!(gamma_powers))
+            .map(|(t_2_j, gamma_j)| t_2_j.mul(gamma_j))
+            .sum();
+
+        Ok((E::G1::normalize_batch(&qs), t_2.into_affine()))
+    }
+}
+
+#[cfg(test)]
+mod columns_test {
+    use super::{MultiColumnQs, MultiColumnTable};
+    use crate::{compute_qs, compute_tau_powers, SetupError};
+    use ark_bn254::{Bn254, Fr, G2Projective};
+    use ark_ec::{CurveGroup, Group};
+    use ark_ff::UniformRand;
+    use ark_poly::{EvaluationDomain, GeneralEvaluationDomain, Polynomial};
+    use ark_std::test_rng;
+    use std::ops::Mul;
+
+    // cargo test --features=parallel test_multi_column_qs
+    #[test]
+    fn test_multi_column_qs() {
+        let k = 4;
+        let n = 1 << k;
+        let mut rng = test_rng();
+        let tau = Fr::rand(&mut rng);
+        let gamma = Fr::rand(&mut rng);
+
+        let domain = GeneralEvaluationDomain::<Fr>::new(n).unwrap();
+        let tau_powers = compute_tau_powers(tau, n);
+
+        let columns: Vec<Vec<Fr>> = (0..3)
+            .map(|_| (0..n).map(|_| Fr::rand(&mut rng)).collect())
+            .collect();
+        let table = MultiColumnTable::from_evaluations(&domain, &columns).unwrap();
+
+        let column_qs = MultiColumnQs::<Bn254>::compute(&table, &domain, &tau_powers).unwrap();
+        let (qs, t_2) = column_qs.combine(gamma).unwrap();
+
+        let combined = table.combine(gamma);
+        let expected_evals: Vec<Fr> = (0..n)
+            .map(|i| columns[0][i] + gamma * columns[1][i] + gamma * gamma * columns[2][i])
+            .collect();
+        assert_eq!(domain.fft(&combined.coeffs), expected_evals);
+
+        assert_eq!(
+            qs,
+            compute_qs::<Bn254>(&combined, &domain, &tau_powers).unwrap()
+        );
+        assert_eq!(
+            t_2,
+            G2Projective::generator()
+                .mul(combined.evaluate(&tau))
+                .into_affine()
+        );
+
+        let empty = MultiColumnQs::<Bn254> {
+            qs: vec![],
+            t_2: vec![],
+        };
+        assert!(matches!(empty.combine(gamma), Err(SetupError::Empty)));
+        let mut ragged = column_qs;
+        ragged.qs[1].pop();
+        assert!(matches!(
+            ragged.combine(gamma),
+            Err(SetupError::SizeMismatch {
+                expected: 16,
+                found: 15
+            })
+        ));
+    }
+}
@@ -6,8 +6,17 @@ use std::ops::Mul;
 #[cfg(feature = "parallel")]
 use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 
+use crate::error::{check_tau, SetupError};
 use crate::utils::is_pow_2;
 
+/// Evaluates the polynomial with `coeffs` at tau, given the powers of tau
+pub(crate) fn evaluate_at_tau<F: Field>(coeffs: &[F], powers_of_tau: &[F]) -> F {
+    cfg_iter!(coeffs)
+        .zip(cfg_iter!(powers_of_tau))
+        .map(|(&t_i, tau_pow_i)| t_i * tau_pow_i)
+        .sum()
+}
+
 pub struct CommonPreprocessedInput<E: Pairing> {
     pub(crate) zv_2: E::G2Affine,
     pub(crate) t_2: E::G2Affine,
@@ -16,38 +25,141 @@ pub struct CommonPreprocessedInput<E: Pairing> {
 }
 
 impl<E: Pairing> CommonPreprocessedInput<E> {
-    fn compute(
+    /// `table_size` is the size of the table subgroup, which may be smaller than the SRS.
+    /// `powers_of_tau` may be longer than the table, the extra powers are ignored.
+    pub fn compute(
         powers_of_tau: &[E::ScalarField],
         table_coeffs: &[E::ScalarField],
+        table_size: usize,
         srs_g1_len: usize,
         circuit_domain: usize,
-    ) -> Self {
-        assert_eq!(powers_of_tau.len(), table_coeffs.len());
-        assert!(is_pow_2(srs_g1_len));
+    ) -> Result<Self, SetupError> {
+        for size in [srs_g1_len, table_size, circuit_domain] {
+            if !is_pow_2(size) {
+                return Err(SetupError::NotPow2 { size });
+            }
+        }
+        // B0 has degree n - 2
+        if circuit_domain < 2 {
+            return Err(SetupError::CircuitTooSmall {
+                size: circuit_domain,
+            });
+        }
+        for (found, expected) in [
+            (table_size, srs_g1_len),
+            (circuit_domain, srs_g1_len),
+            (table_coeffs.len(), table_size),
+        ] {
+            if found > expected {
+                return Err(SetupError::SizeMismatch { expected, found });
+            }
+        }
+        if powers_of_tau.len() < table_size.max(2) {
+            return Err(SetupError::SizeMismatch {
+                expected: table_size.max(2),
+                found: powers_of_tau.len(),
+            });
+        }
 
         let tau = powers_of_tau[1];
+        check_tau(tau, table_size)?;
         let g2 = E::G2::generator();
 
-        // zv_2 = x^n - 1
-        // force that g2_srs is always 1 more longer than g1_srs
-        let zv = tau.pow(&[(srs_g1_len + 1 - 1) as u64]) - E::ScalarField::one();
+        // zv_2 = x^N - 1, vanishes on the table subgroup
+        let zv = tau.pow([table_size as u64]) - E::ScalarField::one();
         let zv_2: E::G2Affine = g2.mul(zv).into();
 
-        let table_at_tau: E::ScalarField = cfg_iter!(table_coeffs)
-            .zip(cfg_iter!(powers_of_tau))
-            .map(|(&t_i, tau_pow_i)| t_i * tau_pow_i)
-            .sum();
+        let table_at_tau = evaluate_at_tau(table_coeffs, &powers_of_tau[..table_coeffs.len()]);
 
         let t_2: E::G2Affine = g2.mul(table_at_tau).into();
 
         let b0_bound_index = srs_g1_len - 1 - (circuit_domain - 2);
-        let x_b0_bound: E::G2Affine = g2.mul(tau.pow(&[b0_bound_index as u64])).into();
+        let x_b0_bound: E::G2Affine = g2.mul(tau.pow([b0_bound_index as u64])).into();
 
-        Self {
+        Ok(Self {
             zv_2,
             t_2,
             x_b0_bound,
             srs_g1_len,
-        }
+        })
+    }
+
+    /// [zv(tau)]_2
+    pub fn zv_2(&self) -> E::G2Affine {
+        self.zv_2
+    }
+
+    /// [t(tau)]_2
+    pub fn t_2(&self) -> E::G2Affine {
+        self.t_2
+    }
+
+    /// [tau^{N - 1 - (n - 2)}]_2
+    pub fn x_b0_bound(&self) -> E::G2Affine {
+        self.x_b0_bound
+    }
+
+    pub fn srs_g1_len(&self) -> usize {
+        self.srs_g1_len
+    }
+}
+
+#[cfg(test)]
+mod common_test {
+    use super::CommonPreprocessedInput;
+    use crate::{compute_tau_powers, gen_table, SetupError};
+    use ark_bn254::{Bn254, Fr, G2Projective};
+    use ark_ec::{CurveGroup, Group};
+    use ark_ff::{Field, One};
+    use ark_poly::Polynomial;
+    use std::ops::Mul;
+
+    #[test]
+    fn test_table_smaller_than_srs() {
+        let tau = Fr::from(100u64);
+        let (table_n, srs_n, circuit_n) = (1 << 3, 1 << 6, 1 << 4);
+        let powers_of_tau = compute_tau_powers(tau, srs_n);
+        let t = gen_table::<Fr>(3, Some("small table")).unwrap();
+
+        let cpi = CommonPreprocessedInput::<Bn254>::compute(
+            &powers_of_tau,
+            &t.coeffs,
+            table_n,
+            srs_n,
+            circuit_n,
+        )
+        .unwrap();
+
+        let g2 = G2Projective::generator();
+        let zv = tau.pow([table_n as u64]) - Fr::one();
+        assert_eq!(cpi.zv_2(), g2.mul(zv).into_affine());
+        assert_eq!(cpi.t_2(), g2.mul(t.evaluate(&tau)).into_affine());
+        let b0_bound_index = (srs_n - 1 - (circuit_n - 2)) as u64;
+        assert_eq!(
+            cpi.x_b0_bound(),
+            g2.mul(tau.pow([b0_bound_index])).into_affine()
+        );
+
+        let compute = |table_n, circuit_n| {
+            CommonPreprocessedInput::<Bn254>::compute(
+                &powers_of_tau,
+                &t.coeffs,
+                table_n,
+                srs_n,
+                circuit_n,
+            )
+        };
+        assert!(matches!(
+            compute(table_n, 1),
+            Err(SetupError::CircuitTooSmall { size: 1 })
+        ));
+        assert!(matches!(
+            compute(2 * srs_n, circuit_n),
+            Err(SetupError::SizeMismatch { .. })
+        ));
+        assert!(matches!(
+            compute(table_n, 12),
+            Err(SetupError::NotPow2 { size: 12 })
+        ));
     }
 }
@@ -0,0 +1,251 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.19;
+
+/// @title Verifier of cq lookups into one fixed table over BN254
+/// @notice Generated by `cq-setup export-solidity` for the table with fingerprint {table},
+/// N = {table_n} and circuits of size n = {circuit_n}.
+///
+/// Fiat-Shamir transcript. Every value is a 32 byte big-endian word, a G1 point is written as
+/// x || y, and each challenge is the Keccak-256 hash reduced mod r:
+///   beta  = keccak256(DOMAIN || TABLE || N || n || f || m)
+///   gamma = keccak256(beta || A || Q_A || B0 || Q_B || P)
+///   eta   = keccak256(gamma || b0(gamma) || f(gamma) || a(0))
+/// where DOMAIN = keccak256("mock-cq-setup/cq_transcript/v1") and TABLE is the table fingerprint.
+///
+/// G2 points are in the order of the pairing precompile (EIP-197): x.c1, x.c0, y.c1, y.c0.
+contract CqVerifier {
+    /// base field modulus
+    uint256 constant Q = {q};
+    /// scalar field modulus
+    uint256 constant R = {r};
+
+    bytes32 constant DOMAIN = keccak256("mock-cq-setup/cq_transcript/v1");
+    bytes32 constant TABLE = {table};
+    uint256 constant TABLE_N = {table_n};
+    uint256 constant CIRCUIT_N = {circuit_n};
+    uint256 constant CIRCUIT_K = {circuit_k};
+    /// N / n mod r, B(0) = N * A(0) / n
+    uint256 constant B0_SCALE = {b0_scale};
+
+    // [1]_2
+    uint256 constant G2_X1 = {g2_x1};
+    uint256 constant G2_X0 = {g2_x0};
+    uint256 constant G2_Y1 = {g2_y1};
+    uint256 constant G2_Y0 = {g2_y0};
+    // [tau]_2
+    uint256 constant TAU_2_X1 = {tau_2_x1};
+    uint256 constant TAU_2_X0 = {tau_2_x0};
+    uint256 constant TAU_2_Y1 = {tau_2_y1};
+    uint256 constant TAU_2_Y0 = {tau_2_y0};
+    // [zv(tau)]_2 = [tau^N - 1]_2
+    uint256 constant ZV_2_X1 = {zv_2_x1};
+    uint256 constant ZV_2_X0 = {zv_2_x0};
+    uint256 constant ZV_2_Y1 = {zv_2_y1};
+    uint256 constant ZV_2_Y0 = {zv_2_y0};
+    // [T(tau)]_2
+    uint256 constant T_2_X1 = {t_2_x1};
+    uint256 constant T_2_X0 = {t_2_x0};
+    uint256 constant T_2_Y1 = {t_2_y1};
+    uint256 constant T_2_Y0 = {t_2_y0};
+    // [tau^{max(N, n) - 1 - (n - 2)}]_2
+    uint256 constant X_B0_BOUND_X1 = {x_b0_bound_x1};
+    uint256 constant X_B0_BOUND_X0 = {x_b0_bound_x0};
+    uint256 constant X_B0_BOUND_Y1 = {x_b0_bound_y1};
+    uint256 constant X_B0_BOUND_Y0 = {x_b0_bound_y0};
+
+    /// G1 points are (x, y), the point at infinity is (0, 0)
+    struct Proof {
+        uint256[2] m;
+        uint256[2] a;
+        uint256[2] qa;
+        uint256[2] b0;
+        uint256[2] qb;
+        /// [B0(tau) * tau^{max(N, n) - 1 - (n - 2)}]_1, bounds the degree of B0
+        uint256[2] p;
+        uint256 b0Gamma;
+        uint256 fGamma;
+        uint256 a0;
+        /// opening of B0 + eta * f + eta^2 * Q_B at gamma
+        uint256[2] piGamma;
+        /// opening of A at 0
+        uint256[2] a0Proof;
+    }
+
+    struct Challenges {
+        uint256 beta;
+        uint256 gamma;
+        uint256 eta;
+    }
+
+    /// @notice Checks that every entry of the committed column f is in the table.
+    /// Reverts on points that are not on the curve and on scalars that are not reduced.
+    function verify(uint256[2] calldata f, Proof calldata proof) external view returns (bool) {
+        require(proof.b0Gamma < R && proof.fGamma < R && proof.a0 < R, "cq: scalar not reduced");
+        Challenges memory ch = challenges(f, proof);
+        return checkTableQuotient(proof, ch.beta) && checkB0Degree(proof)
+            && checkOpeningsAtGamma(f, proof, ch) && checkOpeningAtZero(proof);
+    }
+
+    function challenges(uint256[2] calldata f, Proof calldata proof)
+        internal
+        pure
+        returns (Challenges memory ch)
+    {
+        ch.beta = uint256(keccak256(abi.encode(DOMAIN, TABLE, TABLE_N, CIRCUIT_N, f, proof.m))) % R;
+        ch.gamma =
+            uint256(keccak256(abi.encode(ch.beta, proof.a, proof.qa, proof.b0, proof.qb, proof.p))) % R;
+        ch.eta = uint256(keccak256(abi.encode(ch.gamma, proof.b0Gamma, proof.fGamma, proof.a0))) % R;
+    }
+
+    /// e(A, [T]_2) = e(Q_A, [zv]_2) * e(m - beta * A, [1]_2)
+    function checkTableQuotient(Proof calldata proof, uint256 beta) internal view returns (bool) {
+        uint256[2][] memory g1s = new uint256[2][](3);
+        uint256[4][] memory g2s = new uint256[4][](3);
+        g1s[0] = proof.a;
+        g2s[0] = t2();
+        g1s[1] = negate(proof.qa);
+        g2s[1] = zv2();
+        g1s[2] = ecAdd(ecMul(proof.a, beta), negate(proof.m));
+        g2s[2] = g2();
+        return pairing(g1s, g2s);
+    }
+
+    /// e(B0, [tau^{max(N, n) - 1 - (n - 2)}]_2) = e(P, [1]_2)
+    function checkB0Degree(Proof calldata proof) internal view returns (bool) {
+        uint256[2][] memory g1s = new uint256[2][](2);
+        uint256[4][] memory g2s = new uint256[4][](2);
+        g1s[0] = proof.b0;
+        g2s[0] = xB0Bound();
+        g1s[1] = negate(proof.p);
+        g2s[1] = g2();
+        return pairing(g1s, g2s);
+    }
+
+    /// Q_B(gamma) = ((B0(gamma) * gamma + B(0)) * (f(gamma) + beta) - 1) / (gamma^n - 1), and the
+    /// batched opening e(C - c * [1]_1 + gamma * pi, [1]_2) = e(pi, [tau]_2) with
+    /// C = B0 + eta * f + eta^2 * Q_B and c = B0(gamma) + eta * f(gamma) + eta^2 * Q_B(gamma)
+    function checkOpeningsAtGamma(uint256[2] calldata f, Proof calldata proof, Challenges memory ch)
+        internal
+        view
+        returns (bool)
+    {
+        uint256 qbGamma = quotientAtGamma(proof, ch);
+        uint256 eta2 = mulmod(ch.eta, ch.eta, R);
+        uint256 c = addmod(addmod(proof.b0Gamma, mulmod(ch.eta, proof.fGamma, R), R), mulmod(eta2, qbGamma, R), R);
+        uint256[2] memory lhs = ecAdd(ecAdd(proof.b0, ecMul(f, ch.eta)), ecMul(proof.qb, eta2));
+        lhs = ecAdd(ecAdd(lhs, negate(ecMul([uint256(1), 2], c))), ecMul(proof.piGamma, ch.gamma));
+
+        uint256[2][] memory g1s = new uint256[2][](2);
+        uint256[4][] memory g2s = new uint256[4][](2);
+        g1s[0] = lhs;
+        g2s[0] = g2();
+        g1s[1] = negate(proof.piGamma);
+        g2s[1] = tau2();
+        return pairing(g1s, g2s);
+    }
+
+    function quotientAtGamma(Proof calldata proof, Challenges memory ch) internal view returns (uint256) {
+        uint256 b0 = mulmod(proof.a0, B0_SCALE, R);
+        uint256 b = addmod(mulmod(proof.b0Gamma, ch.gamma, R), b0, R);
+        uint256 zh = addmod(powCircuitN(ch.gamma), R - 1, R);
+        return mulmod(addmod(mulmod(b, addmod(proof.fGamma, ch.beta, R), R), R - 1, R), inverse(zh), R);
+    }
+
+    /// e(A - a(0) * [1]_1, [1]_2) = e(pi_0, [tau]_2)
+    function checkOpeningAtZero(Proof calldata proof) internal view returns (bool) {
+        uint256[2][] memory g1s = new uint256[2][](2);
+        uint256[4][] memory g2s = new uint256[4][](2);
+        g1s[0] = ecAdd(proof.a, negate(ecMul([uint256(1), 2], proof.a0)));
+        g2s[0] = g2();
+        g1s[1] = negate(proof.a0Proof);
+        g2s[1] = tau2();
+        return pairing(g1s, g2s);
+    }
+
+    function g2() internal pure returns (uint256[4] memory) {
+        return [G2_X1, G2_X0, G2_Y1, G2_Y0];
+    }
+
+    function tau2() internal pure returns (uint256[4] memory) {
+        return [TAU_2_X1, TAU_2_X0, TAU_2_Y1, TAU_2_Y0];
+    }
+
+    function zv2() internal pure returns (uint256[4] memory) {
+        return [ZV_2_X1, ZV_2_X0, ZV_2_Y1, ZV_2_Y0];
+    }
+
+    function t2() internal pure returns (uint256[4] memory) {
+        return [T_2_X1, T_2_X0, T_2_Y1, T_2_Y0];
+    }
+
+    function xB0Bound() internal pure returns (uint256[4] memory) {
+        return [X_B0_BOUND_X1, X_B0_BOUND_X0, X_B0_BOUND_Y1, X_B0_BOUND_Y0];
+    }
+
+    /// x^n by squaring, n is a power of two
+    function powCircuitN(uint256 x) internal pure returns (uint256) {
+        for (uint256 i = 0; i < CIRCUIT_K; i++) {
+            x = mulmod(x, x, R);
+        }
+        return x;
+    }
+
+    /// x^(r - 2) with the modexp precompile
+    function inverse(uint256 x) internal view returns (uint256 result) {
+        require(x != 0, "cq: inverse of zero");
+        bool success;
+        assembly {
+            let p := mload(0x40)
+            mstore(p, 0x20)
+            mstore(add(p, 0x20), 0x20)
+            mstore(add(p, 0x40), 0x20)
+            mstore(add(p, 0x60), x)
+            mstore(add(p, 0x80), sub(R, 2))
+            mstore(add(p, 0xa0), R)
+            success := staticcall(gas(), 0x05, p, 0xc0, p, 0x20)
+            result := mload(p)
+        }
+        require(success, "cq: modexp failed");
+    }
+
+    function negate(uint256[2] memory p) internal pure returns (uint256[2] memory) {
+        return [p[0], (Q - p[1]) % Q];
+    }
+
+    function ecAdd(uint256[2] memory a, uint256[2] memory b) internal view returns (uint256[2] memory result) {
+        uint256[4] memory input = [a[0], a[1], b[0], b[1]];
+        bool success;
+        assembly {
+            success := staticcall(gas(), 0x06, input, 0x80, result, 0x40)
+        }
+        require(success, "cq: invalid G1 point");
+    }
+
+    function ecMul(uint256[2] memory p, uint256 s) internal view returns (uint256[2] memory result) {
+        uint256[3] memory input = [p[0], p[1], s];
+        bool success;
+        assembly {
+            success := staticcall(gas(), 0x07, input, 0x60, result, 0x40)
+        }
+        require(success, "cq: invalid G1 point");
+    }
+
+    /// Whether the product of e(g1s[i], g2s[i]) is one
+    function pairing(uint256[2][] memory g1s, uint256[4][] memory g2s) internal view returns (bool) {
+        uint256[] memory input = new uint256[](6 * g1s.length);
+        for (uint256 i = 0; i < g1s.length; i++) {
+            input[6 * i] = g1s[i][0];
+            input[6 * i + 1] = g1s[i][1];
+            for (uint256 j = 0; j < 4; j++) {
+                input[6 * i + 2 + j] = g2s[i][j];
+            }
+        }
+        uint256[1] memory out;
+        bool success;
+        assembly {
+            success := staticcall(gas(), 0x08, add(input, 0x20), mul(mload(input), 0x20), out, 0x20)
+        }
+        require(success, "cq: invalid pairing input");
+        return out[0] == 1;
+    }
+}
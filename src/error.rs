@@ -0,0 +1,220 @@
+use crate::evm::EvmError;
+use crate::fingerprint::TableFingerprint;
+use crate::import::ImportError;
+use crate::setup_file::{Section, SetupFileError};
+use crate::table_file::TableFileError;
+use ark_ff::{FftField, Field};
+use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+use ark_serialize::SerializationError;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum SetupError {
+    /// tau is zero or a root of unity of one of the domains, the setup would divide by zero
+    /// or publish a trivial SRS
+    DegenerateTau,
+    /// size that has to be a power of two is not
+    NotPow2 {
+        size: usize,
+    },
+    /// the scalar field has no subgroup of size 2^k
+    UnsupportedSize {
+        k: usize,
+    },
+    /// input holds `found` elements where `expected` are needed
+    SizeMismatch {
+        expected: usize,
+        found: usize,
+    },
+    /// circuits need at least 2 rows, B0 has degree n - 2
+    CircuitTooSmall {
+        size: usize,
+    },
+    /// changed table entry outside of the table
+    IndexOutOfRange {
+        index: usize,
+        len: usize,
+    },
+    /// no tables or columns were given
+    Empty,
+    /// value `index` of the union source `name` does not fit below the tag shift
+    UnionValueTooLarge {
+        name: String,
+        index: usize,
+    },
+    /// a setup section fails its consistency check
+    Inconsistent {
+        section: Section,
+    },
+    /// a setup file or verifier key was computed for another table, or records none
+    TableMismatch {
+        expected: TableFingerprint,
+        found: Option<TableFingerprint>,
+    },
+    /// the setup is predicted to need more memory than is available
+    InsufficientMemory {
+        needed: u64,
+        available: u64,
+    },
+    /// command line arguments that do not form a valid invocation
+    Usage(String),
+    /// error in the file at `path`
+    File {
+        path: String,
+        err: Box<SetupError>,
+    },
+    Io(std::io::Error),
+    /// bytes that do not deserialize into the expected points or field elements
+    Malformed(SerializationError),
+    TableFile(TableFileError),
+    SetupFile(SetupFileError),
+    Import(ImportError),
+    Evm(EvmError),
+}
+
+impl fmt::Display for SetupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DegenerateTau => write!(f, "tau is zero or a root of unity of the domain"),
+            Self::NotPow2 { size } => write!(f, "size {} is not a power of two", size),
+            Self::UnsupportedSize { k } => {
+                write!(f, "the scalar field has no subgroup of size 2^{}", k)
+            }
+            Self::SizeMismatch { expected, found } => {
+                write!(f, "expected {} elements but found {}", expected, found)
+            }
+            Self::CircuitTooSmall { size } => {
+                write!(f, "circuit size {} is smaller than 2", size)
+            }
+            Self::IndexOutOfRange { index, len } => {
+                write!(f, "index {} is out of range for {} entries", index, len)
+            }
+            Self::Empty => write!(f, "no tables were given"),
+            Self::UnionValueTooLarge { name, index } => write!(
+                f,
+                "value {} of {} is not below 2^{}, the tag shift of the union",
+                index,
+                name,
+                crate::union::TAG_SHIFT_BITS
+            ),
+            Self::Inconsistent { section } => {
+                write!(f, "{} is inconsistent with the rest of the setup", section)
+            }
+            Self::TableMismatch {
+                expected,
+                found: Some(found),
+            } => write!(
+                f,
+                "computed for table {} but the table is {}",
+                found, expected
+            ),
+            Self::TableMismatch {
+                expected,
+                found: None,
+            } => write!(f, "records no table, expected table {}", expected),
+            Self::InsufficientMemory { needed, available } => write!(
+                f,
+                "setup needs about {:.1} MiB but only {:.1} MiB is available",
+                *needed as f64 / (1u64 << 20) as f64,
+                *available as f64 / (1u64 << 20) as f64
+            ),
+            Self::Usage(usage) => write!(f, "usage: {}", usage),
+            Self::File { path, err } => write!(f, "{}: {}", path, err),
+            Self::Io(err) => write!(f, "io error: {}", err),
+            Self::Malformed(err) => write!(f, "malformed data: {}", err),
+            Self::TableFile(err) => write!(f, "{}", err),
+            Self::SetupFile(err) => write!(f, "{}", err),
+            Self::Import(err) => write!(f, "{}", err),
+            Self::Evm(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for SetupError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::File { err, .. } => Some(err.as_ref()),
+            Self::Io(err) => Some(err),
+            Self::Malformed(err) => Some(err),
+            Self::TableFile(err) => Some(err),
+            Self::SetupFile(err) => Some(err),
+            Self::Import(err) => Some(err),
+            Self::Evm(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl SetupError {
+    /// Adds the path of the file the error comes from
+    pub fn in_file(path: &str) -> impl FnOnce(SetupError) -> SetupError + '_ {
+        move |err| Self::File {
+            path: path.to_string(),
+            err: Box::new(err),
+        }
+    }
+}
+
+impl From<std::io::Error> for SetupError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<SerializationError> for SetupError {
+    fn from(err: SerializationError) -> Self {
+        Self::Malformed(err)
+    }
+}
+
+impl From<TableFileError> for SetupError {
+    fn from(err: TableFileError) -> Self {
+        Self::TableFile(err)
+    }
+}
+
+impl From<SetupFileError> for SetupError {
+    fn from(err: SetupFileError) -> Self {
+        Self::SetupFile(err)
+    }
+}
+
+impl From<ImportError> for SetupError {
+    fn from(err: ImportError) -> Self {
+        Self::Import(err)
+    }
+}
+
+impl From<EvmError> for SetupError {
+    fn from(err: EvmError) -> Self {
+        Self::Evm(err)
+    }
+}
+
+/// Domain of size `n`, which has to be a power of two supported by the field
+pub(crate) fn domain<F: FftField>(n: usize) -> Result<GeneralEvaluationDomain<F>, SetupError> {
+    if !n.is_power_of_two() {
+        return Err(SetupError::NotPow2 { size: n });
+    }
+    GeneralEvaluationDomain::new(n).ok_or(SetupError::UnsupportedSize {
+        k: n.trailing_zeros() as usize,
+    })
+}
+
+/// Domain of size 2^k
+pub(crate) fn domain_of_log_size<F: FftField>(
+    k: usize,
+) -> Result<GeneralEvaluationDomain<F>, SetupError> {
+    let n = 1usize
+        .checked_shl(k as u32)
+        .ok_or(SetupError::UnsupportedSize { k })?;
+    domain(n)
+}
+
+/// Rejects tau = 0 and tau with tau^n = 1, which lies on every subgroup of size dividing n
+pub(crate) fn check_tau<F: Field>(tau: F, n: usize) -> Result<(), SetupError> {
+    if tau.is_zero() || tau.pow([n as u64]).is_one() {
+        return Err(SetupError::DegenerateTau);
+    }
+    Ok(())
+}
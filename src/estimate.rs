@@ -0,0 +1,278 @@
+use crate::report::num_threads;
+use crate::setup::Stage;
+use ark_ec::{pairing::Pairing, Group};
+use ark_ff::{Field, UniformRand};
+use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+use std::hint::black_box;
+use std::mem::size_of;
+use std::ops::Mul;
+use std::time::{Duration, Instant};
+
+/// Single threaded cost of the operations the setup is made of, measured on this machine
+#[derive(Clone, Copy, Debug)]
+pub struct Calibration {
+    pub field_mul: Duration,
+    pub field_inverse: Duration,
+    pub g1_mul: Duration,
+    pub g2_mul: Duration,
+    /// cost of a single butterfly, fft of size n costs `n/2 * log(n)` butterflies
+    pub fft_butterfly: Duration,
+}
+
+const CALIBRATION_FIELD_OPS: u32 = 1 << 16;
+const CALIBRATION_INVERSES: u32 = 1 << 10;
+const CALIBRATION_SCALAR_MULS: u32 = 1 << 6;
+const CALIBRATION_FFT_LOG_SIZE: u32 = 14;
+
+impl Calibration {
+    /// Runs short micro benchmarks, takes well under a second
+    pub fn measure<E: Pairing>() -> Self {
+        let mut rng = rand::thread_rng();
+        let x = E::ScalarField::rand(&mut rng);
+
+        let now = Instant::now();
+        let mut acc = x;
+        for _ in 0..CALIBRATION_FIELD_OPS {
+            acc *= x;
+        }
+        black_box(acc);
+        let field_mul = now.elapsed() / CALIBRATION_FIELD_OPS;
+
+        let now = Instant::now();
+        let mut acc = x;
+        for _ in 0..CALIBRATION_INVERSES {
+            acc = acc.inverse().unwrap() + x;
+        }
+        black_box(acc);
+        let field_inverse = now.elapsed() / CALIBRATION_INVERSES;
+
+        let now = Instant::now();
+        let mut acc = E::G1::generator();
+        for _ in 0..CALIBRATION_SCALAR_MULS {
+            acc = acc.mul(x);
+        }
+        black_box(acc);
+        let g1_mul = now.elapsed() / CALIBRATION_SCALAR_MULS;
+
+        let now = Instant::now();
+        let mut acc = E::G2::generator();
+        for _ in 0..CALIBRATION_SCALAR_MULS {
+            acc = acc.mul(x);
+        }
+        black_box(acc);
+        let g2_mul = now.elapsed() / CALIBRATION_SCALAR_MULS;
+
+        let n = 1usize << CALIBRATION_FFT_LOG_SIZE;
+        let domain = GeneralEvaluationDomain::<E::ScalarField>::new(n).unwrap();
+        let coeffs: Vec<E::ScalarField> = (0..n).map(|_| E::ScalarField::rand(&mut rng)).collect();
+        let now = Instant::now();
+        black_box(serial(|| domain.fft(&coeffs)));
+        let fft_butterfly = now.elapsed() / (n as u32 / 2 * CALIBRATION_FFT_LOG_SIZE);
+
+        Self {
+            field_mul,
+            field_inverse,
+            g1_mul,
+            g2_mul,
+            fft_butterfly,
+        }
+    }
+
+    fn fft(&self, log_size: usize) -> Duration {
+        let butterflies = (1u64 << log_size) as f64 / 2.0 * log_size as f64;
+        self.fft_butterfly.mul_f64(butterflies)
+    }
+
+    /// Single threaded time of `stage` when it runs over 2^k elements, see `stage_log_size`
+    pub fn stage_time(&self, stage: Stage, k: usize) -> Duration {
+        let n = (1u64 << k) as f64;
+        let field_muls = |count: usize| self.field_mul * count as u32;
+        match stage {
+            Stage::TauPowers => self.field_mul.mul_f64(n),
+            Stage::LagrangeCommitments | Stage::CircuitLagrangeCommitments => {
+                (self.g1_mul + self.field_inverse + field_muls(k + 3)).mul_f64(n)
+            }
+            Stage::LagrangeOpeningsAtZero => {
+                (self.g1_mul + field_muls(k + 6)).mul_f64(n) + self.field_inverse
+            }
+            Stage::G1Powers => self.g1_mul.mul_f64(n),
+            Stage::G2Powers => self.g2_mul.mul_f64(n + 1.0),
+            // 3 circulant ffts of size 2n, the fft of hs and a scalar mul per q
+            Stage::Qs => {
+                self.fft(k + 1) * 3 + self.fft(k) + (self.g1_mul + field_muls(2)).mul_f64(n)
+            }
+        }
+    }
+}
+
+/// The calibration has to measure a single thread even in parallel builds
+fn serial<T: Send>(f: impl FnOnce() -> T + Send) -> T {
+    #[cfg(feature = "parallel")]
+    let res = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap()
+        .install(f);
+
+    #[cfg(not(feature = "parallel"))]
+    let res = f();
+
+    res
+}
+
+/// log2 of the number of elements `stage` runs over, the SRS powers cover both the table and the circuit
+pub fn stage_log_size(stage: Stage, table_k: usize, circuit_k: usize) -> usize {
+    match stage {
+        Stage::CircuitLagrangeCommitments => circuit_k,
+        Stage::G1Powers | Stage::G2Powers => table_k.max(circuit_k),
+        _ => table_k,
+    }
+}
+
+/// Predicted wall time and memory of a single stage
+#[derive(Clone, Copy, Debug)]
+pub struct StageEstimate {
+    pub stage: Stage,
+    pub time: Duration,
+    /// memory held while the stage is running, including its output
+    pub working_memory_bytes: u64,
+    /// memory still held by the output once the stage is done
+    pub output_bytes: u64,
+}
+
+/// Predicted cost of the whole setup for tables of size 2^table_k and circuits of size 2^circuit_k
+#[derive(Clone, Debug)]
+pub struct SetupEstimate {
+    pub table_k: usize,
+    pub circuit_k: usize,
+    pub threads: usize,
+    pub stages: Vec<StageEstimate>,
+    pub total_time: Duration,
+    pub peak_memory_bytes: u64,
+}
+
+/// Time and memory of every stage for tables of size 2^table_k and circuits of size 2^circuit_k,
+/// assuming a full size tau. Without calibration only the memory is estimated and all times are zero.
+pub fn estimate_setup<E: Pairing>(
+    table_k: usize,
+    circuit_k: usize,
+    calibration: Option<&Calibration>,
+) -> SetupEstimate {
+    let threads = num_threads();
+
+    let f = size_of::<E::ScalarField>() as u64;
+    let g1 = size_of::<E::G1>() as u64;
+    let g1_affine = size_of::<E::G1Affine>() as u64;
+    let g2 = size_of::<E::G2>() as u64;
+    let g2_affine = size_of::<E::G2Affine>() as u64;
+
+    let stages: Vec<StageEstimate> = Stage::ALL
+        .iter()
+        .map(|&stage| {
+            let k = stage_log_size(stage, table_k, circuit_k);
+            let n = 1u64 << k;
+            let time = calibration.map_or(Duration::ZERO, |c| c.stage_time(stage, k));
+
+            let (working_memory_bytes, output_bytes) = match stage {
+                Stage::TauPowers => (n * f, n * f),
+                Stage::LagrangeCommitments | Stage::CircuitLagrangeCommitments => {
+                    (n * (g1 + g1_affine), n * g1_affine)
+                }
+                Stage::LagrangeOpeningsAtZero => (n * (f + g1 + g1_affine), n * g1_affine),
+                Stage::G1Powers => (n * (g1 + g1_affine), n * g1_affine),
+                Stage::G2Powers => ((n + 1) * (g2 + g2_affine), (n + 1) * g2_affine),
+                // toeplitz and circulant representations, 2n sized fft buffers, hs and ks
+                Stage::Qs => (14 * n * f + n * (g1 + g1_affine), n * g1_affine),
+            };
+
+            StageEstimate {
+                stage,
+                time: time / threads as u32,
+                working_memory_bytes,
+                output_bytes,
+            }
+        })
+        .collect();
+
+    let total_time = stages.iter().map(|stage| stage.time).sum();
+
+    // the table is held for the whole run
+    let table_bytes = (1u64 << table_k) * f;
+    let outputs: u64 = stages.iter().map(|stage| stage.output_bytes).sum();
+
+    // in the worst case every stage is in flight at the same time, otherwise they run one by one
+    let transient = stages
+        .iter()
+        .map(|stage| stage.working_memory_bytes - stage.output_bytes);
+    let transient = if cfg!(feature = "parallel") {
+        transient.sum()
+    } else {
+        transient.max().unwrap_or(0)
+    };
+
+    SetupEstimate {
+        table_k,
+        circuit_k,
+        threads,
+        stages,
+        total_time,
+        peak_memory_bytes: table_bytes + outputs + transient,
+    }
+}
+
+/// Memory that can be allocated without swapping, if the platform reports it
+pub fn available_memory_bytes() -> Option<u64> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    let line = meminfo
+        .lines()
+        .find(|line| line.starts_with("MemAvailable:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+#[cfg(test)]
+mod estimate_test {
+    use super::{estimate_setup, Calibration};
+    use crate::setup::Stage;
+    use ark_bn254::Bn254;
+
+    #[test]
+    fn test_memory_estimate_scales_with_k() {
+        let small = estimate_setup::<Bn254>(10, 10, None);
+        let large = estimate_setup::<Bn254>(11, 11, None);
+
+        assert_eq!(small.stages.len(), Stage::ALL.len());
+        assert!(small.total_time.is_zero());
+        // G2 powers are 1 longer, so sizes grow by slightly less than 2
+        for (s, l) in small.stages.iter().zip(large.stages.iter()) {
+            assert!(3 * s.output_bytes < 2 * l.output_bytes);
+            assert!(l.output_bytes <= 2 * s.output_bytes);
+        }
+        assert!(3 * small.peak_memory_bytes < 2 * large.peak_memory_bytes);
+    }
+
+    #[test]
+    fn test_calibrated_estimate() {
+        let calibration = Calibration::measure::<Bn254>();
+        assert!(calibration.g1_mul > calibration.field_mul);
+        assert!(calibration.g2_mul > calibration.field_mul);
+
+        let estimate = estimate_setup::<Bn254>(8, 8, Some(&calibration));
+        assert!(estimate.stages.iter().all(|stage| !stage.time.is_zero()));
+    }
+
+    #[test]
+    fn test_circuit_size_estimate() {
+        let square = estimate_setup::<Bn254>(10, 10, None);
+        let large_circuit = estimate_setup::<Bn254>(10, 12, None);
+
+        for (s, l) in square.stages.iter().zip(large_circuit.stages.iter()) {
+            match s.stage {
+                Stage::CircuitLagrangeCommitments | Stage::G1Powers | Stage::G2Powers => {
+                    assert!(l.output_bytes > 3 * s.output_bytes)
+                }
+                _ => assert_eq!(l.output_bytes, s.output_bytes),
+            }
+        }
+    }
+}
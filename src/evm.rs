@@ -0,0 +1,763 @@
+use crate::fingerprint::TableFingerprint;
+use crate::setup_file::{Section, SetupFile};
+use crate::update::TableCommitments;
+use crate::vk::VerifierKey;
+use ark_bn254::{g1, g2, Bn254, Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_ec::{short_weierstrass::Affine, AffineRepr};
+use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use num_bigint::BigUint;
+use serde_json::{json, Value};
+use std::fmt;
+
+/*
+    The EVM precompiles (EIP-196, EIP-197) take every coordinate as a 32 byte big-endian word,
+    the point at infinity as all zeros, and an Fq2 element c0 + c1 * u as c1 followed by c0,
+    the reverse of the arkworks order. The JSON and ABI encodings use the same words, with G2
+    coordinates in either order:
+
+        Fr              "n"
+        G1              ["x", "y"]
+        G2, evm         [["x.c1", "x.c0"], ["y.c1", "y.c0"]]
+        G2, arkworks    [["x.c0", "x.c1"], ["y.c0", "y.c1"]]
+
+    The ABI encoding of a value is its words, as the static type `uint256[2]`, `uint256[4]` or
+    a struct of them, and a section is the dynamic array `T[]`.
+*/
+
+/// How JSON writes integers, both are accepted when reading
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NumberFormat {
+    /// decimal strings
+    Decimal,
+    /// `0x` followed by 64 hex digits
+    Hex,
+}
+
+impl NumberFormat {
+    pub const ALL: [NumberFormat; 2] = [NumberFormat::Decimal, NumberFormat::Hex];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NumberFormat::Decimal => "decimal",
+            NumberFormat::Hex => "hex",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|numbers| numbers.name() == name)
+    }
+}
+
+/// Order of the two coordinates of an Fq2 element
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum G2Order {
+    /// c1, c0 as the pairing precompile takes them
+    Evm,
+    /// c0, c1 as arkworks stores them
+    Arkworks,
+}
+
+impl G2Order {
+    pub const ALL: [G2Order; 2] = [G2Order::Evm, G2Order::Arkworks];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            G2Order::Evm => "evm",
+            G2Order::Arkworks => "arkworks",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|order| order.name() == name)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct JsonFormat {
+    pub numbers: NumberFormat,
+    pub g2_order: G2Order,
+}
+
+#[derive(Debug)]
+pub enum EvmError {
+    Json(serde_json::Error),
+    /// `field` is missing or not of the expected JSON type
+    Malformed {
+        field: String,
+    },
+    /// not a decimal or `0x` hex integer below the modulus
+    InvalidInteger {
+        value: String,
+    },
+    /// coordinates of a point that is not in the prime order subgroup
+    InvalidPoint,
+    /// ABI data of `found` bytes where `expected` are needed
+    AbiLength {
+        expected: usize,
+        found: usize,
+    },
+    /// `found` words where `expected` are needed
+    WordCount {
+        expected: usize,
+        found: usize,
+    },
+    /// only bn254 has EVM precompiles
+    UnsupportedCurve(String),
+}
+
+impl fmt::Display for EvmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(err) => write!(f, "invalid json: {}", err),
+            Self::Malformed { field } => write!(f, "`{}` is missing or malformed", field),
+            Self::InvalidInteger { value } => {
+                write!(f, "`{}` is not an integer below the modulus", value)
+            }
+            Self::InvalidPoint => write!(f, "coordinates are not a point of the subgroup"),
+            Self::AbiLength { expected, found } => {
+                write!(
+                    f,
+                    "expected {} bytes of abi data but found {}",
+                    expected, found
+                )
+            }
+            Self::WordCount { expected, found } => {
+                write!(f, "expected {} words but found {}", expected, found)
+            }
+            Self::UnsupportedCurve(curve) => {
+                write!(f, "curve {} has no EVM precompiles, only bn254 has", curve)
+            }
+        }
+    }
+}
+
+impl std::error::Error for EvmError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Json(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for EvmError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
+    }
+}
+
+fn malformed(field: &str) -> EvmError {
+    EvmError::Malformed {
+        field: field.to_string(),
+    }
+}
+
+fn word_to_json(x: &BigUint, numbers: NumberFormat) -> Value {
+    match numbers {
+        NumberFormat::Decimal => Value::String(x.to_string()),
+        NumberFormat::Hex => Value::String(format!("0x{:064x}", x)),
+    }
+}
+
+/// Decimal or `0x` hex string, or a json number
+fn word_from_json(value: &Value, field: &str) -> Result<BigUint, EvmError> {
+    let word = match value {
+        Value::Number(n) => n.as_u64().map(BigUint::from),
+        Value::String(s) => match s.strip_prefix("0x") {
+            Some(digits) => BigUint::parse_bytes(digits.as_bytes(), 16),
+            None => BigUint::parse_bytes(s.as_bytes(), 10),
+        },
+        _ => None,
+    };
+    word.ok_or_else(|| malformed(field))
+}
+
+fn field_from_word<F: PrimeField>(x: &BigUint) -> Result<F, EvmError> {
+    if *x >= F::MODULUS.into() {
+        return Err(EvmError::InvalidInteger {
+            value: x.to_string(),
+        });
+    }
+    Ok(F::from(x.clone()))
+}
+
+fn u64_from_word(x: &BigUint) -> Result<u64, EvmError> {
+    u64::try_from(x).map_err(|_| EvmError::InvalidInteger {
+        value: x.to_string(),
+    })
+}
+
+fn fingerprint_from_word(x: &BigUint) -> Result<TableFingerprint, EvmError> {
+    let bytes = x.to_bytes_be();
+    if bytes.len() > 32 {
+        return Err(EvmError::InvalidInteger {
+            value: x.to_string(),
+        });
+    }
+    let mut fingerprint = [0u8; 32];
+    fingerprint[32 - bytes.len()..].copy_from_slice(&bytes);
+    Ok(TableFingerprint(fingerprint))
+}
+
+fn words_to_bytes(words: &[BigUint]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(32 * words.len());
+    for word in words {
+        let word = word.to_bytes_be();
+        bytes.resize(bytes.len() + 32 - word.len(), 0);
+        bytes.extend_from_slice(&word);
+    }
+    bytes
+}
+
+fn words_from_bytes(bytes: &[u8]) -> Vec<BigUint> {
+    bytes.chunks(32).map(BigUint::from_bytes_be).collect()
+}
+
+/// Words of nested json arrays, in order
+fn flatten(value: &Value, field: &str, words: &mut Vec<BigUint>) -> Result<(), EvmError> {
+    match value {
+        Value::Array(values) => values
+            .iter()
+            .try_for_each(|value| flatten(value, field, words)),
+        _ => {
+            words.push(word_from_json(value, field)?);
+            Ok(())
+        }
+    }
+}
+
+fn fq_to_uint(x: &Fq) -> BigUint {
+    x.into_bigint().into()
+}
+
+/// (x, y) in the order of the precompiles
+pub fn g1_to_evm(p: &G1Affine) -> [BigUint; 2] {
+    match p.xy() {
+        Some((x, y)) => [fq_to_uint(x), fq_to_uint(y)],
+        None => Default::default(),
+    }
+}
+
+/// (x.c1, x.c0, y.c1, y.c0) in the order of the pairing precompile
+pub fn g2_to_evm(p: &G2Affine) -> [BigUint; 4] {
+    match p.xy() {
+        Some((x, y)) => [
+            fq_to_uint(&x.c1),
+            fq_to_uint(&x.c0),
+            fq_to_uint(&y.c1),
+            fq_to_uint(&y.c0),
+        ],
+        None => Default::default(),
+    }
+}
+
+/// Values written as 32 byte words, in json and in ABI encoded calldata
+pub trait EvmEncode: Sized {
+    /// number of words of the ABI encoding
+    const WORDS: usize;
+
+    fn to_words(&self, order: G2Order) -> Vec<BigUint>;
+
+    /// Decodes exactly `WORDS` words, which `from_words` checks before calling it
+    fn from_exact_words(words: &[BigUint], order: G2Order) -> Result<Self, EvmError>;
+
+    /// Rejects a word count other than `WORDS`, integers that are not reduced and points
+    /// outside of the prime order subgroup
+    fn from_words(words: &[BigUint], order: G2Order) -> Result<Self, EvmError> {
+        if words.len() != Self::WORDS {
+            return Err(EvmError::WordCount {
+                expected: Self::WORDS,
+                found: words.len(),
+            });
+        }
+        Self::from_exact_words(words, order)
+    }
+
+    fn to_json(&self, format: JsonFormat) -> Value;
+
+    fn from_json(value: &Value, order: G2Order) -> Result<Self, EvmError> {
+        let mut words = vec![];
+        flatten(value, "value", &mut words)?;
+        if words.len() != Self::WORDS {
+            return Err(malformed("value"));
+        }
+        Self::from_words(&words, order)
+    }
+
+    fn to_abi(&self, order: G2Order) -> Vec<u8> {
+        words_to_bytes(&self.to_words(order))
+    }
+
+    fn from_abi(bytes: &[u8], order: G2Order) -> Result<Self, EvmError> {
+        if bytes.len() != 32 * Self::WORDS {
+            return Err(EvmError::AbiLength {
+                expected: 32 * Self::WORDS,
+                found: bytes.len(),
+            });
+        }
+        Self::from_words(&words_from_bytes(bytes), order)
+    }
+}
+
+impl EvmEncode for Fr {
+    const WORDS: usize = 1;
+
+    fn to_words(&self, _order: G2Order) -> Vec<BigUint> {
+        vec![self.into_bigint().into()]
+    }
+
+    fn from_exact_words(words: &[BigUint], _order: G2Order) -> Result<Self, EvmError> {
+        field_from_word(&words[0])
+    }
+
+    fn to_json(&self, format: JsonFormat) -> Value {
+        word_to_json(&self.into_bigint().into(), format.numbers)
+    }
+}
+
+// implemented on `Affine<Config>`, the `G1Affine` and `G2Affine` aliases look alike to coherence
+impl EvmEncode for Affine<g1::Config> {
+    const WORDS: usize = 2;
+
+    fn to_words(&self, _order: G2Order) -> Vec<BigUint> {
+        g1_to_evm(self).to_vec()
+    }
+
+    fn from_exact_words(words: &[BigUint], _order: G2Order) -> Result<Self, EvmError> {
+        if words.iter().all(|word| *word == BigUint::default()) {
+            return Ok(G1Affine::zero());
+        }
+        let p = G1Affine::new_unchecked(field_from_word(&words[0])?, field_from_word(&words[1])?);
+        if !p.is_on_curve() || !p.is_in_correct_subgroup_assuming_on_curve() {
+            return Err(EvmError::InvalidPoint);
+        }
+        Ok(p)
+    }
+
+    fn to_json(&self, format: JsonFormat) -> Value {
+        let words = self.to_words(format.g2_order);
+        json!([
+            word_to_json(&words[0], format.numbers),
+            word_to_json(&words[1], format.numbers)
+        ])
+    }
+}
+
+impl EvmEncode for Affine<g2::Config> {
+    const WORDS: usize = 4;
+
+    fn to_words(&self, order: G2Order) -> Vec<BigUint> {
+        let [x1, x0, y1, y0] = g2_to_evm(self);
+        match order {
+            G2Order::Evm => vec![x1, x0, y1, y0],
+            G2Order::Arkworks => vec![x0, x1, y0, y1],
+        }
+    }
+
+    fn from_exact_words(words: &[BigUint], order: G2Order) -> Result<Self, EvmError> {
+        if words.iter().all(|word| *word == BigUint::default()) {
+            return Ok(G2Affine::zero());
+        }
+        let fq2 = |a: &BigUint, b: &BigUint| -> Result<Fq2, EvmError> {
+            let (c0, c1) = match order {
+                G2Order::Evm => (b, a),
+                G2Order::Arkworks => (a, b),
+            };
+            Ok(Fq2::new(field_from_word(c0)?, field_from_word(c1)?))
+        };
+        let p = G2Affine::new_unchecked(fq2(&words[0], &words[1])?, fq2(&words[2], &words[3])?);
+        if !p.is_on_curve() || !p.is_in_correct_subgroup_assuming_on_curve() {
+            return Err(EvmError::InvalidPoint);
+        }
+        Ok(p)
+    }
+
+    fn to_json(&self, format: JsonFormat) -> Value {
+        let words: Vec<Value> = self
+            .to_words(format.g2_order)
+            .iter()
+            .map(|word| word_to_json(word, format.numbers))
+            .collect();
+        json!([[words[0], words[1]], [words[2], words[3]]])
+    }
+}
+
+/// The key as the struct `(uint256 tableN, uint256 circuitN, bytes32 table, uint256 srsG1Len,
+/// uint256[2] g1, uint256[4] g2, uint256[4] tau2, uint256[4] zv2, uint256[4] t2,
+/// uint256[4] xB0Bound)`
+impl EvmEncode for VerifierKey<Bn254> {
+    const WORDS: usize = 26;
+
+    fn to_words(&self, order: G2Order) -> Vec<BigUint> {
+        let mut words = vec![
+            BigUint::from(self.table_n),
+            BigUint::from(self.circuit_n),
+            BigUint::from_bytes_be(&self.table.0),
+            BigUint::from(self.srs_g1_len),
+        ];
+        words.extend(self.g1.to_words(order));
+        for point in [self.g2, self.tau_2, self.zv_2, self.t_2, self.x_b0_bound] {
+            words.extend(point.to_words(order));
+        }
+        words
+    }
+
+    fn from_exact_words(words: &[BigUint], order: G2Order) -> Result<Self, EvmError> {
+        let g2 = |i: usize| G2Affine::from_words(&words[i..i + 4], order);
+        Ok(Self {
+            table_n: u64_from_word(&words[0])?,
+            circuit_n: u64_from_word(&words[1])?,
+            table: fingerprint_from_word(&words[2])?,
+            srs_g1_len: u64_from_word(&words[3])?,
+            g1: G1Affine::from_words(&words[4..6], order)?,
+            g2: g2(6)?,
+            tau_2: g2(10)?,
+            zv_2: g2(14)?,
+            t_2: g2(18)?,
+            x_b0_bound: g2(22)?,
+        })
+    }
+
+    fn to_json(&self, format: JsonFormat) -> Value {
+        json!({
+            "table_n": self.table_n,
+            "circuit_n": self.circuit_n,
+            "table": format!("0x{}", self.table),
+            "srs_g1_len": self.srs_g1_len,
+            "g1": self.g1.to_json(format),
+            "g2": self.g2.to_json(format),
+            "tau_2": self.tau_2.to_json(format),
+            "zv_2": self.zv_2.to_json(format),
+            "t_2": self.t_2.to_json(format),
+            "x_b0_bound": self.x_b0_bound.to_json(format),
+        })
+    }
+
+    fn from_json(value: &Value, order: G2Order) -> Result<Self, EvmError> {
+        let field = |name: &str| value.get(name).ok_or_else(|| malformed(name));
+        let size = |name: &str| u64_from_word(&word_from_json(field(name)?, name)?);
+        let g2 = |name: &str| G2Affine::from_json(field(name)?, order);
+        Ok(Self {
+            table_n: size("table_n")?,
+            circuit_n: size("circuit_n")?,
+            table: fingerprint_from_word(&word_from_json(field("table")?, "table")?)?,
+            srs_g1_len: size("srs_g1_len")?,
+            g1: G1Affine::from_json(field("g1")?, order)?,
+            g2: g2("g2")?,
+            tau_2: g2("tau_2")?,
+            zv_2: g2("zv_2")?,
+            t_2: g2("t_2")?,
+            x_b0_bound: g2("x_b0_bound")?,
+        })
+    }
+}
+
+/// ABI encoding of `values` as the dynamic array `T[]`: offset, length and the elements
+pub fn abi_encode<T: EvmEncode>(values: &[T], order: G2Order) -> Vec<u8> {
+    let mut words = vec![BigUint::from(32u64)];
+    words.extend(array_words(values, order));
+    words_to_bytes(&words)
+}
+
+pub fn abi_decode<T: EvmEncode>(bytes: &[u8], order: G2Order) -> Result<Vec<T>, EvmError> {
+    let offset = abi_offset(bytes, 0)?;
+    decode_array(&bytes[offset..], order)
+}
+
+/// Length followed by the elements, the tail of a dynamic array
+fn array_words<T: EvmEncode>(values: &[T], order: G2Order) -> Vec<BigUint> {
+    let mut words = vec![BigUint::from(values.len())];
+    for value in values {
+        words.extend(value.to_words(order));
+    }
+    words
+}
+
+/// Offset stored in the word at `head`, checked to lie within `bytes`
+fn abi_offset(bytes: &[u8], head: usize) -> Result<usize, EvmError> {
+    let word = bytes.get(head..head + 32).ok_or(EvmError::AbiLength {
+        expected: head + 32,
+        found: bytes.len(),
+    })?;
+    usize::try_from(BigUint::from_bytes_be(word))
+        .ok()
+        .filter(|offset| offset % 32 == 0 && *offset <= bytes.len())
+        .ok_or_else(|| malformed("offset"))
+}
+
+fn decode_array<T: EvmEncode>(tail: &[u8], order: G2Order) -> Result<Vec<T>, EvmError> {
+    let len = tail.get(..32).map(BigUint::from_bytes_be);
+    let len = len
+        .and_then(|len| usize::try_from(len).ok())
+        .ok_or_else(|| malformed("length"))?;
+    let size = 32 * T::WORDS;
+    let expected = len
+        .checked_mul(size)
+        .and_then(|data| data.checked_add(32))
+        .ok_or_else(|| malformed("length"))?;
+    if tail.len() != expected {
+        return Err(EvmError::AbiLength {
+            expected,
+            found: tail.len(),
+        });
+    }
+    tail[32..]
+        .chunks(size)
+        .map(|chunk| T::from_abi(chunk, order))
+        .collect()
+}
+
+/// As the struct `(uint256[2] t1, uint256[4] t2, uint256[2][] qs)`
+impl TableCommitments<Bn254> {
+    pub fn to_json(&self, format: JsonFormat) -> Value {
+        let qs: Vec<Value> = self.qs.iter().map(|q| q.to_json(format)).collect();
+        json!({
+            "t_1": self.t_1.to_json(format),
+            "t_2": self.t_2.to_json(format),
+            "qs": qs,
+        })
+    }
+
+    pub fn from_json(value: &Value, order: G2Order) -> Result<Self, EvmError> {
+        let field = |name: &str| value.get(name).ok_or_else(|| malformed(name));
+        let qs = field("qs")?.as_array().ok_or_else(|| malformed("qs"))?;
+        Ok(Self {
+            qs: qs
+                .iter()
+                .map(|q| G1Affine::from_json(q, order))
+                .collect::<Result<_, _>>()?,
+            t_1: G1Affine::from_json(field("t_1")?, order)?,
+            t_2: G2Affine::from_json(field("t_2")?, order)?,
+        })
+    }
+
+    pub fn to_abi(&self, order: G2Order) -> Vec<u8> {
+        let mut words = self.t_1.to_words(order);
+        words.extend(self.t_2.to_words(order));
+        // the head is 7 words long, qs follows it
+        words.push(BigUint::from(7 * 32u64));
+        words.extend(array_words(&self.qs, order));
+        words_to_bytes(&words)
+    }
+
+    pub fn from_abi(bytes: &[u8], order: G2Order) -> Result<Self, EvmError> {
+        let offset = abi_offset(bytes, 6 * 32)?;
+        Ok(Self {
+            t_1: G1Affine::from_abi(&bytes[..64], order)?,
+            t_2: G2Affine::from_abi(&bytes[64..192], order)?,
+            qs: decode_array(&bytes[offset..], order)?,
+        })
+    }
+}
+
+/// Json document of a setup file, with its curve, section and table
+pub fn setup_file_to_json<T>(file: &SetupFile<T>, format: JsonFormat) -> Value
+where
+    T: EvmEncode + CanonicalSerialize + CanonicalDeserialize + Send,
+{
+    let values: Vec<Value> = file.data.iter().map(|x| x.to_json(format)).collect();
+    json!({
+        "curve": file.header.curve,
+        "section": file.header.section.name(),
+        "table": file.header.table.map(|table| format!("0x{}", table)),
+        "g2_order": format.g2_order.name(),
+        "values": values,
+    })
+}
+
+/// Reads a document written by `setup_file_to_json`
+pub fn setup_file_from_json<T>(document: &Value) -> Result<SetupFile<T>, EvmError>
+where
+    T: EvmEncode + CanonicalSerialize + CanonicalDeserialize + Send,
+{
+    let string = |name: &str| {
+        document
+            .get(name)
+            .and_then(Value::as_str)
+            .ok_or_else(|| malformed(name))
+    };
+    let curve = string("curve")?;
+    if curve != "bn254" {
+        return Err(EvmError::UnsupportedCurve(curve.to_string()));
+    }
+    let section = Section::from_name(string("section")?).ok_or_else(|| malformed("section"))?;
+    let order = G2Order::from_name(string("g2_order")?).ok_or_else(|| malformed("g2_order"))?;
+    let values = document
+        .get("values")
+        .and_then(Value::as_array)
+        .ok_or_else(|| malformed("values"))?;
+    let data = values
+        .iter()
+        .map(|value| T::from_json(value, order))
+        .collect::<Result<_, _>>()?;
+
+    let file = SetupFile::new(curve, section, data);
+    Ok(match document.get("table") {
+        None | Some(Value::Null) => file,
+        Some(table) => file.for_table(fingerprint_from_word(&word_from_json(table, "table")?)?),
+    })
+}
+
+#[cfg(test)]
+mod evm_test {
+    use super::{
+        abi_decode, abi_encode, g1_to_evm, g2_to_evm, setup_file_from_json, setup_file_to_json,
+        EvmEncode, EvmError, G2Order, JsonFormat, NumberFormat,
+    };
+    use crate::{
+        compute_tau_powers, gen_table, setup, Section, SetupFile, TableCommitments,
+        TableFingerprint, VerifierKey,
+    };
+    use ark_bn254::{Bn254, Fr, G1Affine, G2Affine};
+    use ark_ec::AffineRepr;
+    use ark_ff::PrimeField;
+    use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+    use num_bigint::BigUint;
+    use serde_json::json;
+
+    const FORMATS: [JsonFormat; 4] = [
+        JsonFormat {
+            numbers: NumberFormat::Decimal,
+            g2_order: G2Order::Evm,
+        },
+        JsonFormat {
+            numbers: NumberFormat::Hex,
+            g2_order: G2Order::Evm,
+        },
+        JsonFormat {
+            numbers: NumberFormat::Decimal,
+            g2_order: G2Order::Arkworks,
+        },
+        JsonFormat {
+            numbers: NumberFormat::Hex,
+            g2_order: G2Order::Arkworks,
+        },
+    ];
+
+    #[test]
+    fn test_points() {
+        // generators as listed in EIP-197
+        let (x1, x0, y1, y0) = (
+            "11559732032986387107991004021392285783925812861821192530917403151452391805634",
+            "10857046999023057135944570762232829481370756359578518086990519993285655852781",
+            "4082367875863433681332203403145435568316851327593401208105741076214120093531",
+            "8495653923123431417604973247489272438418190587263600148770280649306958101930",
+        );
+        let g2 = G2Affine::generator();
+        let evm: Vec<String> = g2_to_evm(&g2).iter().map(|x| x.to_string()).collect();
+        assert_eq!(evm, [x1, x0, y1, y0]);
+        assert_eq!(
+            g1_to_evm(&G1Affine::generator()),
+            [1u64, 2].map(BigUint::from)
+        );
+        assert_eq!(g2.to_json(FORMATS[0]), json!([[x1, x0], [y1, y0]]));
+        assert_eq!(g2.to_json(FORMATS[2]), json!([[x0, x1], [y0, y1]]));
+        assert_eq!(
+            G1Affine::generator().to_json(FORMATS[1]),
+            json!([format!("0x{:064x}", 1), format!("0x{:064x}", 2)])
+        );
+
+        for format in FORMATS {
+            let order = format.g2_order;
+            for p in [g2, G2Affine::zero()] {
+                assert_eq!(G2Affine::from_json(&p.to_json(format), order).unwrap(), p);
+                assert_eq!(G2Affine::from_abi(&p.to_abi(order), order).unwrap(), p);
+            }
+            let x = Fr::from(12345u64);
+            assert_eq!(Fr::from_json(&x.to_json(format), order).unwrap(), x);
+        }
+
+        // swapping the order of the coordinates does not give a point of the subgroup
+        let swapped = g2.to_json(FORMATS[2]);
+        assert!(matches!(
+            G2Affine::from_json(&swapped, G2Order::Evm),
+            Err(EvmError::InvalidPoint)
+        ));
+        assert!(matches!(
+            G1Affine::from_json(&json!(["1", "3"]), G2Order::Evm),
+            Err(EvmError::InvalidPoint)
+        ));
+        let r: BigUint = Fr::MODULUS.into();
+        assert!(matches!(
+            Fr::from_json(&json!(r.to_string()), G2Order::Evm),
+            Err(EvmError::InvalidInteger { .. })
+        ));
+        for words in [vec![], vec![BigUint::from(1u64)]] {
+            assert!(matches!(
+                G1Affine::from_words(&words, G2Order::Evm),
+                Err(EvmError::WordCount { expected: 2, .. })
+            ));
+            assert!(matches!(
+                VerifierKey::<Bn254>::from_words(&words, G2Order::Evm),
+                Err(EvmError::WordCount { expected: 26, .. })
+            ));
+        }
+        for numbers in NumberFormat::ALL {
+            assert_eq!(NumberFormat::from_name(numbers.name()), Some(numbers));
+        }
+    }
+
+    #[test]
+    fn test_artifacts() {
+        let tau = Fr::from(100u64);
+        let (table_n, circuit_n) = (1 << 3, 1 << 4);
+        let t = gen_table::<Fr>(3, Some("evm")).unwrap();
+        let output = setup::<Bn254>(tau, table_n, circuit_n, &t).unwrap();
+        let fingerprint = TableFingerprint::from_poly("bn254", &t, table_n).unwrap();
+        let vk =
+            VerifierKey::<Bn254>::from_srs(&output.g2_powers, &t, table_n, circuit_n, fingerprint)
+                .unwrap();
+        let domain = GeneralEvaluationDomain::<Fr>::new(table_n).unwrap();
+        let commitments =
+            TableCommitments::<Bn254>::compute(&t, &domain, &compute_tau_powers(tau, table_n))
+                .unwrap();
+
+        for format in FORMATS {
+            let order = format.g2_order;
+            let json = vk.to_json(format);
+            assert_eq!(json["table"], json!(format!("0x{}", fingerprint)));
+            assert_eq!(VerifierKey::from_json(&json, order).unwrap(), vk);
+            assert_eq!(VerifierKey::from_abi(&vk.to_abi(order), order).unwrap(), vk);
+            let json = serde_json::to_string(&commitments.to_json(format)).unwrap();
+            let json = serde_json::from_str(&json).unwrap();
+            assert_eq!(
+                TableCommitments::from_json(&json, order).unwrap(),
+                commitments
+            );
+            let abi = commitments.to_abi(order);
+            assert_eq!(
+                TableCommitments::from_abi(&abi, order).unwrap(),
+                commitments
+            );
+
+            let abi = abi_encode(&output.qs, order);
+            assert_eq!(abi.len(), 64 + 64 * table_n);
+            assert_eq!(abi_decode::<G1Affine>(&abi, order).unwrap(), output.qs);
+            assert!(matches!(
+                abi_decode::<G1Affine>(&abi[..abi.len() - 32], order),
+                Err(EvmError::AbiLength { .. })
+            ));
+
+            let file = SetupFile::new("bn254", Section::G2Powers, output.g2_powers.clone())
+                .for_table(fingerprint);
+            let document = setup_file_to_json(&file, format);
+            let read = setup_file_from_json::<G2Affine>(&document).unwrap();
+            assert_eq!(read.header, file.header);
+            assert_eq!(read.data, file.data);
+        }
+
+        // a single qs entry is a uint256[2]
+        let entry = output.qs[3].to_abi(G2Order::Evm);
+        let [x, y] = g1_to_evm(&output.qs[3]);
+        assert_eq!(BigUint::from_bytes_be(&entry[..32]), x);
+        assert_eq!(BigUint::from_bytes_be(&entry[32..]), y);
+        let untabled = SetupFile::new("bn254", Section::Qs, output.qs.clone());
+        let document = setup_file_to_json(&untabled, FORMATS[0]);
+        assert_eq!(document["table"], json!(null));
+        assert_eq!(
+            setup_file_from_json::<G1Affine>(&document).unwrap().header,
+            untabled.header
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use crate::error::{domain, SetupError};
+use ark_ff::FftField;
+use ark_poly::{univariate::DensePolynomial, EvaluationDomain};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+const FINGERPRINT_DOMAIN: &[u8] = b"mock-cq-setup/table_fingerprint/v1";
+
+/*
+    fingerprint = sha256(FINGERPRINT_DOMAIN || curve || N || evaluations), every part but the
+    domain written with `serialize_compressed`. It is taken over the evaluations on the table
+    domain, so a table gets the same fingerprint in coefficient and in evaluation form.
+*/
+
+/// Identifies the table a setup was computed for
+#[derive(Clone, Copy, PartialEq, Eq, Hash, CanonicalSerialize, CanonicalDeserialize)]
+pub struct TableFingerprint(pub [u8; 32]);
+
+impl TableFingerprint {
+    /// Fingerprint of a table of size `evals.len()`
+    pub fn from_evaluations<F: FftField>(curve: &str, evals: &[F]) -> Self {
+        let mut bytes = FINGERPRINT_DOMAIN.to_vec();
+        curve.to_string().serialize_compressed(&mut bytes).unwrap();
+        (evals.len() as u64)
+            .serialize_compressed(&mut bytes)
+            .unwrap();
+        evals.serialize_compressed(&mut bytes).unwrap();
+        Self(Sha256::digest(&bytes).into())
+    }
+
+    /// Fingerprint of table `t` of size `table_n`
+    pub fn from_poly<F: FftField>(
+        curve: &str,
+        t: &DensePolynomial<F>,
+        table_n: usize,
+    ) -> Result<Self, SetupError> {
+        if t.coeffs.len() > table_n {
+            return Err(SetupError::SizeMismatch {
+                expected: table_n,
+                found: t.coeffs.len(),
+            });
+        }
+        let evals = domain::<F>(table_n)?.fft(&t.coeffs);
+        Ok(Self::from_evaluations(curve, &evals))
+    }
+}
+
+impl fmt::Display for TableFingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|b| write!(f, "{:02x}", b))
+    }
+}
+
+impl fmt::Debug for TableFingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TableFingerprint({})", self)
+    }
+}
+
+#[cfg(test)]
+mod fingerprint_test {
+    use super::TableFingerprint;
+    use crate::gen_table;
+    use ark_bn254::Fr;
+    use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+
+    #[test]
+    fn test_fingerprint() {
+        let t = gen_table::<Fr>(3, Some("fingerprint")).unwrap();
+        let fingerprint = TableFingerprint::from_poly("bn254", &t, 8).unwrap();
+        let evals = GeneralEvaluationDomain::<Fr>::new(8)
+            .unwrap()
+            .fft(&t.coeffs);
+        assert_eq!(
+            TableFingerprint::from_evaluations("bn254", &evals),
+            fingerprint
+        );
+
+        // curve, size and content are all bound
+        assert_ne!(
+            TableFingerprint::from_poly("bls12-381", &t, 8).unwrap(),
+            fingerprint
+        );
+        assert_ne!(
+            TableFingerprint::from_poly("bn254", &t, 16).unwrap(),
+            fingerprint
+        );
+        let other = gen_table::<Fr>(3, Some("other")).unwrap();
+        assert_ne!(
+            TableFingerprint::from_poly("bn254", &other, 8).unwrap(),
+            fingerprint
+        );
+        assert_eq!(fingerprint.to_string().len(), 64);
+    }
+}
@@ -1,29 +1,46 @@
-use crate::utils::{is_pow_2, next_pow2};
+use crate::error::{domain, SetupError};
+use crate::utils::next_pow2;
 use ark_ff::FftField;
 use ark_poly::{
     domain::DomainCoeff, univariate::DensePolynomial, DenseUVPolynomial, EvaluationDomain,
-    GeneralEvaluationDomain, Polynomial,
+    GeneralEvaluationDomain,
 };
-use ark_std::Zero;
+use ark_std::{cfg_iter, cfg_iter_mut, Zero};
 use std::{fmt::Debug, marker::PhantomData};
 
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
 pub struct Circulant<F: FftField, D: DomainCoeff<F> + Debug> {
     _f: PhantomData<F>,
     _d: PhantomData<D>,
 }
 
 impl<F: FftField, D: DomainCoeff<F> + Debug> Circulant<F, D> {
-    pub fn mul_by_vec<T: DomainCoeff<F> + std::ops::MulAssign<D>>(repr: &[D], x: &[T]) -> Vec<T> {
-        assert!(is_pow_2(repr.len()));
-        let domain = GeneralEvaluationDomain::new(repr.len()).unwrap();
-        let v = domain.fft(repr);
-
-        let mut res = domain.fft(x);
-        for (i, _) in x.iter().enumerate() {
-            res[i] *= v[i]
+    pub fn mul_by_vec<T: DomainCoeff<F> + std::ops::MulAssign<D>>(
+        repr: &[D],
+        x: &[T],
+    ) -> Result<Vec<T>, SetupError> {
+        if x.len() != repr.len() {
+            return Err(SetupError::SizeMismatch {
+                expected: repr.len(),
+                found: x.len(),
+            });
         }
+        let domain: GeneralEvaluationDomain<F> = domain(repr.len())?;
+
+        #[cfg(not(feature = "parallel"))]
+        let (v, mut res) = (domain.fft(repr), domain.fft(x));
+
+        // both ffts are independent so they can run side by side
+        #[cfg(feature = "parallel")]
+        let (v, mut res) = rayon::join(|| domain.fft(repr), || domain.fft(x));
 
-        domain.ifft(&res)
+        cfg_iter_mut!(res)
+            .zip(cfg_iter!(v))
+            .for_each(|(res_i, &v_i)| *res_i *= v_i);
+
+        Ok(domain.ifft(&res))
     }
 }
 
@@ -41,16 +58,45 @@ pub struct UpperToeplitz<F: FftField> {
 }
 
 impl<F: FftField> UpperToeplitz<F> {
+    /// Matrix of the smallest power of two size that holds every coefficient of `poly`
     pub fn from_poly(poly: &DensePolynomial<F>) -> Self {
-        let mut repr = poly.coeffs()[1..].to_vec();
-        let next_pow2_degree = next_pow2(poly.degree());
-        let to_extend = vec![F::zero(); next_pow2_degree - poly.degree()];
-        repr.extend_from_slice(&to_extend);
-        assert!(is_pow_2(repr.len()));
+        Self::with_size(poly, next_pow2(poly.coeffs().len()))
+    }
+
+    /// Matrix of size `n`, for any `poly` of degree below `n`. Trailing zero coefficients
+    /// are trimmed by `DensePolynomial`, so the size can not be derived from the degree.
+    pub fn from_poly_with_size(poly: &DensePolynomial<F>, n: usize) -> Result<Self, SetupError> {
+        if !n.is_power_of_two() {
+            return Err(SetupError::NotPow2 { size: n });
+        }
+        if poly.coeffs().len() > n {
+            return Err(SetupError::SizeMismatch {
+                expected: n,
+                found: poly.coeffs().len(),
+            });
+        }
+        Ok(Self::with_size(poly, n))
+    }
+
+    fn with_size(poly: &DensePolynomial<F>, n: usize) -> Self {
+        let mut repr = vec![F::zero(); n];
+        if let Some(coeffs) = poly.coeffs().get(1..) {
+            repr[..coeffs.len()].copy_from_slice(coeffs);
+        }
         Self { repr }
     }
 
-    pub fn mul_by_vec<T: DomainCoeff<F> + std::ops::MulAssign<F> + Zero>(&self, x: &[T]) -> Vec<T> {
+    /// `x` has to be as long as the matrix
+    pub fn mul_by_vec<T: DomainCoeff<F> + std::ops::MulAssign<F> + Zero>(
+        &self,
+        x: &[T],
+    ) -> Result<Vec<T>, SetupError> {
+        if x.len() != self.repr.len() {
+            return Err(SetupError::SizeMismatch {
+                expected: self.repr.len(),
+                found: x.len(),
+            });
+        }
         let circulant_repr = self.to_circulant_repr();
         let zeroes = vec![T::zero(); x.len()];
         Circulant::mul_by_vec(&circulant_repr, &[x, zeroes.as_slice()].concat())
@@ -0,0 +1,332 @@
+use crate::error::{domain, SetupError};
+use crate::table::interpolate_table;
+use crate::utils::is_pow_2;
+use ark_ff::PrimeField;
+use ark_poly::{univariate::DensePolynomial, EvaluationDomain};
+use num_bigint::BigUint;
+use std::fmt;
+use std::path::Path;
+
+/// Layout of a user supplied table file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TableFormat {
+    /// single column csv, the first row is skipped if it is a header without any digits
+    Csv,
+    /// flat json array of strings or integers, integers above 64 bits have to be strings
+    Json,
+    /// one value per line, empty lines and lines starting with `#` are skipped
+    Lines,
+}
+
+impl TableFormat {
+    /// Picks the format from the file extension, anything unknown is read as `Lines`
+    pub fn from_path(path: &str) -> Self {
+        match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+            Some("csv") => Self::Csv,
+            Some("json") => Self::Json,
+            _ => Self::Lines,
+        }
+    }
+}
+
+/// What to do with values outside of `[0, p)`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Overflow {
+    Reduce,
+    Reject,
+}
+
+/// How the table is filled up to the next power of two
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Padding {
+    /// append zeroes, which adds 0 to the lookup set
+    Zero,
+    /// repeat the last entry, which keeps the lookup set unchanged
+    RepeatLast,
+    /// fail unless the table size already is a power of two
+    Exact,
+}
+
+#[derive(Debug)]
+pub enum ImportError {
+    Io(std::io::Error),
+    /// value on `line` (1-based) is not a decimal or hex integer
+    Parse {
+        line: usize,
+        value: String,
+    },
+    /// value on `line` (1-based) does not fit into the field
+    Overflow {
+        line: usize,
+        value: String,
+    },
+    /// table size is not a power of two and padding is `Exact`
+    NotPow2 {
+        len: usize,
+    },
+    Empty,
+    MalformedJson,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "could not read table: {}", err),
+            Self::Parse { line, value } => {
+                write!(
+                    f,
+                    "line {}: `{}` is not a decimal or hex integer",
+                    line, value
+                )
+            }
+            Self::Overflow { line, value } => {
+                write!(f, "line {}: `{}` does not fit into the field", line, value)
+            }
+            Self::NotPow2 { len } => write!(f, "table size {} is not a power of two", len),
+            Self::Empty => write!(f, "table is empty"),
+            Self::MalformedJson => {
+                write!(f, "expected a flat json array of strings and integers")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+impl From<std::io::Error> for ImportError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// Parses an optionally signed decimal or `0x` prefixed hex integer of any size
+fn parse_value<F: PrimeField>(
+    line: usize,
+    value: &str,
+    overflow: Overflow,
+) -> Result<F, ImportError> {
+    let (negative, magnitude) = match value.strip_prefix('-') {
+        Some(magnitude) => (true, magnitude),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+
+    let (radix, digits) = match magnitude
+        .strip_prefix("0x")
+        .or_else(|| magnitude.strip_prefix("0X"))
+    {
+        Some(digits) => (16, digits),
+        None => (10, magnitude),
+    };
+
+    let magnitude =
+        BigUint::parse_bytes(digits.as_bytes(), radix).ok_or_else(|| ImportError::Parse {
+            line,
+            value: value.to_string(),
+        })?;
+
+    let modulus: BigUint = F::MODULUS.into();
+    if overflow == Overflow::Reject && magnitude >= modulus {
+        return Err(ImportError::Overflow {
+            line,
+            value: value.to_string(),
+        });
+    }
+
+    let element = F::from(magnitude);
+    Ok(if negative { -element } else { element })
+}
+
+/// Reads raw table values, without padding
+pub fn parse_table_values<F: PrimeField>(
+    contents: &str,
+    format: TableFormat,
+    overflow: Overflow,
+) -> Result<Vec<F>, ImportError> {
+    // (line, value) pairs
+    let entries: Vec<(usize, String)> = match format {
+        TableFormat::Lines => contents
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(i, line)| (i, line.to_string()))
+            .collect(),
+        TableFormat::Csv => {
+            let mut rows: Vec<(usize, &str)> = contents
+                .lines()
+                .enumerate()
+                .map(|(i, line)| (i + 1, line.trim().trim_end_matches(',').trim()))
+                .filter(|(_, line)| !line.is_empty())
+                .collect();
+            // a header has no digits, so a malformed first value is still reported
+            let has_header = rows
+                .first()
+                .is_some_and(|(_, header)| !header.chars().any(|c| c.is_ascii_digit()));
+            if has_header {
+                rows.remove(0);
+            }
+            rows.into_iter()
+                .map(|(i, row)| (i, unquote(row).to_string()))
+                .collect()
+        }
+        TableFormat::Json => {
+            let array: Vec<serde_json::Value> =
+                serde_json::from_str(contents).map_err(|_| ImportError::MalformedJson)?;
+            // json has no lines, errors point at the position in the array instead.
+            // Integers above 64 bits are parsed as floats and have to be quoted.
+            array
+                .into_iter()
+                .enumerate()
+                .map(|(i, value)| match value {
+                    serde_json::Value::String(value) => Ok((i + 1, value)),
+                    serde_json::Value::Number(number) => Ok((i + 1, number.to_string())),
+                    _ => Err(ImportError::MalformedJson),
+                })
+                .collect::<Result<_, _>>()?
+        }
+    };
+
+    entries
+        .into_iter()
+        .map(|(line, value)| parse_value::<F>(line, &value, overflow))
+        .collect()
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+/// Fills the table up to the next power of two
+pub fn pad_table<F: PrimeField>(
+    mut values: Vec<F>,
+    padding: Padding,
+) -> Result<Vec<F>, ImportError> {
+    let len = values.len();
+    let last = *values.last().ok_or(ImportError::Empty)?;
+    let n = len.next_power_of_two();
+
+    match padding {
+        Padding::Zero => values.resize(n, F::zero()),
+        Padding::RepeatLast => values.resize(n, last),
+        Padding::Exact if !is_pow_2(len) => return Err(ImportError::NotPow2 { len }),
+        Padding::Exact => {}
+    }
+
+    Ok(values)
+}
+
+/// Reads a user supplied table and interpolates it like `gen_table` does.
+/// Returns the table polynomial together with log2 of the padded size.
+pub fn import_table<F: PrimeField>(
+    path: &str,
+    format: TableFormat,
+    overflow: Overflow,
+    padding: Padding,
+) -> Result<(usize, DensePolynomial<F>), SetupError> {
+    let contents = std::fs::read_to_string(path).map_err(ImportError::from)?;
+    let values = parse_table_values::<F>(&contents, format, overflow)?;
+    let t_evals = pad_table(values, padding)?;
+
+    let domain = domain::<F>(t_evals.len())?;
+    let k = domain.log_size_of_group() as usize;
+    Ok((k, interpolate_table(&domain, &t_evals)?))
+}
+
+#[cfg(test)]
+mod import_test {
+    use super::{pad_table, parse_table_values, ImportError, Overflow, Padding, TableFormat};
+    use ark_bn254::Fr;
+    use ark_ff::{BigInteger, PrimeField};
+
+    fn parse(contents: &str, format: TableFormat) -> Vec<Fr> {
+        parse_table_values::<Fr>(contents, format, Overflow::Reject).unwrap()
+    }
+
+    #[test]
+    fn test_formats() {
+        let expected = vec![
+            Fr::from(1u64),
+            Fr::from(255u64),
+            -Fr::from(3u64),
+            Fr::from(u128::MAX),
+        ];
+        let big = u128::MAX.to_string();
+
+        let lines = format!("# opcodes\n1\n0xff\n\n-3\n{}\n", big);
+        assert_eq!(parse(&lines, TableFormat::Lines), expected);
+
+        let csv = format!("value\n1,\n\"0xFF\"\n-3\n{}\n", big);
+        assert_eq!(parse(&csv, TableFormat::Csv), expected);
+
+        let json = format!("[1, \"0xff\", -3, \"{}\"]", big);
+        assert_eq!(parse(&json, TableFormat::Json), expected);
+        // a first row with digits is data, not a header
+        let err = parse_table_values::<Fr>("12a\n1\n", TableFormat::Csv, Overflow::Reject);
+        assert!(matches!(err, Err(ImportError::Parse { line: 1, .. })));
+        assert_eq!(
+            parse("\"0x1\"\n2\n", TableFormat::Csv),
+            [1u64, 2].map(Fr::from)
+        );
+
+        for json in ["[1, [2]]", "[1, true]", "{\"a\": 1}", "[1, 2", "[1,, 2]"] {
+            let err = parse_table_values::<Fr>(json, TableFormat::Json, Overflow::Reject);
+            assert!(matches!(err, Err(ImportError::MalformedJson)), "{}", json);
+        }
+        for json in ["[1, 2.5]".to_string(), format!("[1, {}]", big)] {
+            let err = parse_table_values::<Fr>(&json, TableFormat::Json, Overflow::Reject);
+            assert!(
+                matches!(err, Err(ImportError::Parse { line: 2, .. })),
+                "{}",
+                json
+            );
+        }
+        assert_eq!(parse(" [ ] ", TableFormat::Json), vec![]);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let modulus = Fr::MODULUS.to_string();
+        let contents = format!("1\n{}\n", modulus);
+
+        let err = parse_table_values::<Fr>(&contents, TableFormat::Lines, Overflow::Reject);
+        assert!(matches!(err, Err(ImportError::Overflow { line: 2, .. })));
+
+        let reduced = parse_table_values::<Fr>(&contents, TableFormat::Lines, Overflow::Reduce);
+        assert_eq!(reduced.unwrap(), vec![Fr::from(1u64), Fr::from(0u64)]);
+
+        let hex_modulus = format!("0x{}", hex(&Fr::MODULUS.to_bytes_be()));
+        let err = parse_table_values::<Fr>(&hex_modulus, TableFormat::Lines, Overflow::Reject);
+        assert!(matches!(err, Err(ImportError::Overflow { line: 1, .. })));
+
+        let err = parse_table_values::<Fr>("12a", TableFormat::Lines, Overflow::Reduce);
+        assert!(matches!(err, Err(ImportError::Parse { line: 1, .. })));
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn test_padding() {
+        let values: Vec<Fr> = (1..=5u64).map(Fr::from).collect();
+
+        let zero = pad_table(values.clone(), Padding::Zero).unwrap();
+        assert_eq!(zero.len(), 8);
+        assert_eq!(zero[5..], [Fr::from(0u64); 3]);
+
+        let repeat = pad_table(values.clone(), Padding::RepeatLast).unwrap();
+        assert_eq!(repeat[5..], [Fr::from(5u64); 3]);
+
+        assert!(matches!(
+            pad_table(values, Padding::Exact),
+            Err(ImportError::NotPow2 { len: 5 })
+        ));
+        assert!(matches!(
+            pad_table::<Fr>(vec![], Padding::Zero),
+            Err(ImportError::Empty)
+        ));
+    }
+}
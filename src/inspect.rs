@@ -0,0 +1,227 @@
+use crate::error::SetupError;
+use crate::serialization::{Encoding, Validation};
+use crate::setup_file::{SetupHeader, SETUP_MAGIC};
+use crate::table_file::{TableHeader, TABLE_MAGIC};
+use ark_serialize::{CanonicalDeserialize, SerializationError};
+use sha2::{Digest, Sha256};
+
+/*
+    Every file written by this crate ends with a `Vec<T>`, a u64 length followed by equally
+    sized elements. Both encodings are canonical, so two elements are equal exactly when their
+    bytes are, and files can be compared without knowing the curve.
+*/
+
+/// Header of a table or setup file, told apart by the magic
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArtifactHeader {
+    Table(TableHeader),
+    Setup(SetupHeader),
+    /// bare `Vec<F>` of coefficients, written before tables had a header
+    LegacyTable,
+}
+
+/// The serialized elements of a file, accessed by index
+#[derive(Clone, Copy, Debug)]
+pub struct Elements<'a> {
+    len: usize,
+    stride: usize,
+    encoding: Encoding,
+    bytes: &'a [u8],
+}
+
+impl<'a> Elements<'a> {
+    pub fn parse(data_bytes: &'a [u8], encoding: Encoding) -> Result<Self, SetupError> {
+        let mut reader = data_bytes;
+        let len = u64::deserialize_compressed(&mut reader)? as usize;
+        let stride = reader.len().checked_div(len).unwrap_or(0);
+        if stride * len != reader.len() {
+            return Err(SerializationError::InvalidData.into());
+        }
+        Ok(Self {
+            len,
+            stride,
+            encoding,
+            bytes: reader,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Size of one serialized element in bytes, compressed or uncompressed per the header
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    pub fn bytes(&self, i: usize) -> &'a [u8] {
+        &self.bytes[i * self.stride..(i + 1) * self.stride]
+    }
+
+    pub fn get<T: CanonicalDeserialize>(&self, i: usize) -> Result<T, SetupError> {
+        self.get_with_validation(i, Validation::Validated)
+    }
+
+    pub fn get_with_validation<T: CanonicalDeserialize>(
+        &self,
+        i: usize,
+        validation: Validation,
+    ) -> Result<T, SetupError> {
+        Ok(T::deserialize_with_mode(
+            self.bytes(i),
+            self.encoding.compress(),
+            validation.validate(),
+        )?)
+    }
+}
+
+/// A table or setup file split into its header and elements
+#[derive(Clone, Debug)]
+pub struct Artifact<'a> {
+    pub header: ArtifactHeader,
+    pub elements: Elements<'a>,
+    /// hash of the content, headers record the same hash when the file is intact. Table and
+    /// setup hashes cover the version and header fields as well, legacy table hashes only
+    /// the data.
+    pub content_hash: [u8; 32],
+}
+
+impl<'a> Artifact<'a> {
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, SetupError> {
+        let (header, data_bytes, encoding) = if bytes.starts_with(&SETUP_MAGIC) {
+            let (header, data_bytes) = SetupHeader::parse(bytes)?;
+            let encoding = header.encoding;
+            (ArtifactHeader::Setup(header), data_bytes, encoding)
+        } else if bytes.starts_with(&TABLE_MAGIC) {
+            let (header, data_bytes) = TableHeader::parse(bytes)?;
+            (
+                ArtifactHeader::Table(header),
+                data_bytes,
+                Encoding::Compressed,
+            )
+        } else {
+            (ArtifactHeader::LegacyTable, bytes, Encoding::Compressed)
+        };
+        let content_hash = match &header {
+            ArtifactHeader::Setup(header) => header.content_hash(data_bytes),
+            ArtifactHeader::Table(header) => header.content_hash(data_bytes),
+            _ => Sha256::digest(data_bytes).into(),
+        };
+        Ok(Self {
+            header,
+            elements: Elements::parse(data_bytes, encoding)?,
+            content_hash,
+        })
+    }
+}
+
+/// How two artifacts differ
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArtifactDiff {
+    /// header fields with different values
+    pub fields: Vec<&'static str>,
+    pub lens: (usize, usize),
+    /// index of the first differing element, or the shorter length if one is a prefix
+    pub first_difference: Option<usize>,
+}
+
+impl ArtifactDiff {
+    pub fn is_equal(&self) -> bool {
+        self.fields.is_empty() && self.first_difference.is_none()
+    }
+}
+
+pub fn diff_artifacts(a: &Artifact, b: &Artifact) -> ArtifactDiff {
+    let mut fields = vec![];
+    let mut differs = |field: &'static str, differs: bool| {
+        if differs {
+            fields.push(field);
+        }
+    };
+    match (&a.header, &b.header) {
+        (ArtifactHeader::Table(a), ArtifactHeader::Table(b)) => {
+            differs("curve", a.curve != b.curve);
+            differs("k", a.k != b.k);
+            differs("form", a.form != b.form);
+            differs("padding", a.padding != b.padding);
+            differs("spec", a.spec != b.spec);
+            differs("hash", a.hash != b.hash);
+        }
+        (ArtifactHeader::Setup(a), ArtifactHeader::Setup(b)) => {
+            differs("curve", a.curve != b.curve);
+            differs("section", a.section != b.section);
+            differs("encoding", a.encoding != b.encoding);
+            differs("table", a.table != b.table);
+            differs("len", a.len != b.len);
+            differs("hash", a.hash != b.hash);
+        }
+        (ArtifactHeader::LegacyTable, ArtifactHeader::LegacyTable) => {}
+        _ => differs("kind", true),
+    }
+
+    let (a, b) = (&a.elements, &b.elements);
+    let common = a.len().min(b.len());
+    let first_difference = if a.stride() != b.stride() && common > 0 {
+        Some(0)
+    } else {
+        (0..common)
+            .find(|i| a.bytes(*i) != b.bytes(*i))
+            .or((a.len() != b.len()).then_some(common))
+    };
+    ArtifactDiff {
+        fields,
+        lens: (a.len(), b.len()),
+        first_difference,
+    }
+}
+
+#[cfg(test)]
+mod inspect_test {
+    use super::{diff_artifacts, Artifact, ArtifactHeader};
+    use crate::{compute_g_powers, gen_table, serialize_vec, Section, SetupFile, TableFile};
+    use ark_bn254::{Fr, G1Affine, G1Projective};
+
+    #[test]
+    fn test_inspect_and_diff() {
+        let powers = compute_g_powers::<G1Projective>(Fr::from(100u64), 8);
+        let bytes = SetupFile::new("bn254", Section::G1Powers, powers.clone()).to_bytes();
+        let artifact = Artifact::parse(&bytes).unwrap();
+        assert!(matches!(artifact.header, ArtifactHeader::Setup(_)));
+        assert_eq!(artifact.elements.len(), 8);
+        assert_eq!(artifact.elements.get::<G1Affine>(5).unwrap(), powers[5]);
+        let ArtifactHeader::Setup(header) = &artifact.header else {
+            unreachable!()
+        };
+        assert_eq!(artifact.content_hash, header.hash);
+        assert!(diff_artifacts(&artifact, &artifact).is_equal());
+
+        let mut changed = powers.clone();
+        changed[6] = changed[1];
+        let changed_bytes = SetupFile::new("bn254", Section::G1Powers, changed).to_bytes();
+        let diff = diff_artifacts(&artifact, &Artifact::parse(&changed_bytes).unwrap());
+        assert_eq!(diff.fields, vec!["hash"]);
+        assert_eq!(diff.first_difference, Some(6));
+
+        let shorter = SetupFile::new("bn254", Section::G1Powers, powers[..5].to_vec()).to_bytes();
+        let diff = diff_artifacts(&artifact, &Artifact::parse(&shorter).unwrap());
+        assert_eq!(diff.fields, vec!["len", "hash"]);
+        assert_eq!((diff.lens, diff.first_difference), ((8, 5), Some(5)));
+
+        let t = gen_table::<Fr>(3, Some("inspect")).unwrap();
+        let table_bytes = TableFile::from_poly("bn254", 3, None, "", &t)
+            .unwrap()
+            .to_bytes();
+        let table = Artifact::parse(&table_bytes).unwrap();
+        assert_eq!(table.elements.get::<Fr>(2).unwrap(), t.coeffs[2]);
+        assert_eq!(diff_artifacts(&artifact, &table).fields, vec!["kind"]);
+
+        let legacy_bytes = serialize_vec(&t.coeffs);
+        let legacy = Artifact::parse(&legacy_bytes).unwrap();
+        assert_eq!(legacy.header, ArtifactHeader::LegacyTable);
+        assert_eq!(legacy.elements.len(), t.coeffs.len());
+    }
+}
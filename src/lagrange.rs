@@ -1,12 +1,17 @@
+use crate::error::{check_tau, domain, SetupError};
 #[cfg(feature = "parallel")]
 use crate::utils::parallelize;
 use ark_ec::CurveGroup;
-use ark_ff::{FftField, Field, One};
-use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+use ark_ff::{Field, One};
+use ark_poly::EvaluationDomain;
 
-pub fn lagrange_commitments<G: CurveGroup>(tau: G::ScalarField, n: u64) -> Vec<G::Affine> {
+pub fn lagrange_commitments<G: CurveGroup>(
+    tau: G::ScalarField,
+    n: u64,
+) -> Result<Vec<G::Affine>, SetupError> {
+    let w = domain::<G::ScalarField>(n as usize)?.group_gen();
+    check_tau(tau, n as usize)?;
     let mut g_lagrange_projective = vec![G::zero(); n as usize];
-    let w = G::ScalarField::get_root_of_unity(n).unwrap();
 
     /*
                w^i * zh(X)
@@ -15,12 +20,12 @@ pub fn lagrange_commitments<G: CurveGroup>(tau: G::ScalarField, n: u64) -> Vec<G
     */
 
     let gen = G::generator();
-    let zh = tau.pow(&[n]) - G::ScalarField::one();
+    let zh = tau.pow([n]) - G::ScalarField::one();
     let const_multiplier = zh * (G::ScalarField::from(n).inverse().unwrap());
 
     #[cfg(not(feature = "parallel"))]
     for (i, li) in g_lagrange_projective.iter_mut().enumerate() {
-        let w_pow_i = w.pow(&[i as u64]);
+        let w_pow_i = w.pow([i as u64]);
         *li = gen.mul(w_pow_i * const_multiplier * (tau - w_pow_i).inverse().unwrap());
     }
 
@@ -28,19 +33,20 @@ pub fn lagrange_commitments<G: CurveGroup>(tau: G::ScalarField, n: u64) -> Vec<G
     parallelize(&mut g_lagrange_projective, |g, start| {
         for (idx, g) in g.iter_mut().enumerate() {
             let offset = start + idx;
-            let w_pow_i = w.pow(&[offset as u64]);
+            let w_pow_i = w.pow([offset as u64]);
             *g = gen.mul(w_pow_i * const_multiplier * (tau - w_pow_i).inverse().unwrap());
         }
     });
 
-    G::normalize_batch(&g_lagrange_projective)
+    Ok(G::normalize_batch(&g_lagrange_projective))
 }
 
 pub fn lagrange_openings_commitments_at_zero<G: CurveGroup>(
     tau: G::ScalarField,
     n: usize,
-) -> Vec<G::Affine> {
-    assert!(crate::utils::is_pow_2(n));
+) -> Result<Vec<G::Affine>, SetupError> {
+    let domain = domain::<G::ScalarField>(n)?;
+    check_tau(tau, n)?;
 
     /*
                w^i * zh(X)
@@ -52,22 +58,20 @@ pub fn lagrange_openings_commitments_at_zero<G: CurveGroup>(
                 N * -w^i
     */
 
-    let domain = GeneralEvaluationDomain::<G::ScalarField>::new(n).unwrap();
-
     let lagrange_at_tau = domain.evaluate_all_lagrange_coefficients(tau);
 
     let li_at_zero = G::ScalarField::from(n as u64).inverse().unwrap();
     let w: G::ScalarField = domain.element(1);
     let gen = G::generator();
     // X^{N-1} evaluated at tau
-    let x_to_n_minus_one = tau.pow(&[n as u64 - 1]);
+    let x_to_n_minus_one = tau.pow([n as u64 - 1]);
 
-    let mut lagrange_openings_at_zero = vec![G::zero(); n as usize];
+    let mut lagrange_openings_at_zero = vec![G::zero(); n];
 
     // w^(N - i)*L_i(tau) - 1/N * X^(N-1)
     #[cfg(not(feature = "parallel"))]
     for (i, li) in lagrange_openings_at_zero.iter_mut().enumerate() {
-        let w_inv_pow_i = w.pow(&[(n - i) as u64]);
+        let w_inv_pow_i = w.pow([(n - i) as u64]);
         *li = gen.mul(w_inv_pow_i * lagrange_at_tau[i] - li_at_zero * x_to_n_minus_one);
     }
 
@@ -75,16 +79,17 @@ pub fn lagrange_openings_commitments_at_zero<G: CurveGroup>(
     parallelize(&mut lagrange_openings_at_zero, |g, start| {
         for (idx, g) in g.iter_mut().enumerate() {
             let offset = start + idx;
-            let w_inv_pow_i = w.pow(&[(n - offset) as u64]);
-            *g = gen.mul(w_inv_pow_i * lagrange_at_tau[idx] - li_at_zero * x_to_n_minus_one);
+            let w_inv_pow_i = w.pow([(n - offset) as u64]);
+            *g = gen.mul(w_inv_pow_i * lagrange_at_tau[offset] - li_at_zero * x_to_n_minus_one);
         }
     });
 
-    G::normalize_batch(&lagrange_openings_at_zero)
+    Ok(G::normalize_batch(&lagrange_openings_at_zero))
 }
 
 #[cfg(test)]
 mod test_lagrange {
+    use crate::SetupError;
     use std::ops::Mul;
 
     use ark_bn254::{Fr, G1Affine, G1Projective};
@@ -125,7 +130,7 @@ mod test_lagrange {
     fn compute_lagrange_commitments() {
         let k = 3;
         let n = 1 << k;
-        let tau = Fr::from(100 as u64);
+        let tau = Fr::from(100u64);
 
         let domain = GeneralEvaluationDomain::<Fr>::new(n).unwrap();
         let l_evals = domain.evaluate_all_lagrange_coefficients(tau);
@@ -135,17 +140,43 @@ mod test_lagrange {
         let l_coms: Vec<G1Projective> = l_evals.iter().map(|li| g.mul(li)).collect();
         let l_coms: Vec<G1Affine> = G1Projective::normalize_batch(&l_coms);
 
-        let l_basis_coms = super::lagrange_commitments::<G1Projective>(tau, n as u64);
+        let l_basis_coms = super::lagrange_commitments::<G1Projective>(tau, n as u64).unwrap();
         assert_eq!(l_basis_coms, l_coms);
     }
 
+    #[test]
+    fn degenerate_tau_and_sizes() {
+        // tau = 1 is a root of unity of every domain, tau = -1 of every even sized domain
+        for tau in [Fr::zero(), Fr::one(), -Fr::one()] {
+            assert!(matches!(
+                super::lagrange_commitments::<G1Projective>(tau, 8),
+                Err(SetupError::DegenerateTau)
+            ));
+            assert!(matches!(
+                super::lagrange_openings_commitments_at_zero::<G1Projective>(tau, 8),
+                Err(SetupError::DegenerateTau)
+            ));
+        }
+
+        let tau = Fr::from(100u64);
+        assert!(matches!(
+            super::lagrange_commitments::<G1Projective>(tau, 6),
+            Err(SetupError::NotPow2 { size: 6 })
+        ));
+        // bn254 has two-adicity 28
+        assert!(matches!(
+            super::lagrange_openings_commitments_at_zero::<G1Projective>(tau, 1 << 29),
+            Err(SetupError::UnsupportedSize { k: 29 })
+        ));
+    }
+
     // cargo test --features=parallel compute_lagrange_opening_commitments_at_zero
     #[test]
     fn compute_lagrange_opening_commitments_at_zero() {
         use ark_bn254::{Fr, G1Projective};
         let k = 3;
         let n = 1 << k;
-        let tau = Fr::from(100 as u64);
+        let tau = Fr::from(100u64);
 
         let domain = GeneralEvaluationDomain::<Fr>::new(n).unwrap();
 
@@ -168,7 +199,41 @@ mod test_lagrange {
             .collect();
 
         let lagrange_openings_commitments =
-            super::lagrange_openings_commitments_at_zero::<G1Projective>(tau, n);
+            super::lagrange_openings_commitments_at_zero::<G1Projective>(tau, n).unwrap();
         assert_eq!(lagrange_openings_commitments, q_commitments);
     }
+
+    // cargo test --features=parallel consistency_lagrange
+    #[test]
+    fn consistency_lagrange() {
+        use crate::utils::run_with_threads;
+        use ark_std::UniformRand;
+
+        let mut rng = ark_std::test_rng();
+        for k in 0..=6 {
+            let n = 1 << k;
+            let tau = Fr::rand(&mut rng);
+            let domain = GeneralEvaluationDomain::<Fr>::new(n).unwrap();
+            let l_evals = domain.evaluate_all_lagrange_coefficients(tau);
+            let g = G1Projective::generator();
+
+            let l_coms: Vec<G1Affine> = l_evals.iter().map(|li| g.mul(li).into_affine()).collect();
+            let l_basis_coms = run_with_threads(|| {
+                super::lagrange_commitments::<G1Projective>(tau, n as u64).unwrap()
+            });
+            assert_eq!(l_basis_coms, l_coms);
+
+            // (Li(tau) - Li(0)) / tau, with Li(0) = 1/N
+            let n_inv = Fr::from(n as u64).inverse().unwrap();
+            let tau_inv = tau.inverse().unwrap();
+            let openings: Vec<G1Affine> = l_evals
+                .iter()
+                .map(|li| g.mul((*li - n_inv) * tau_inv).into_affine())
+                .collect();
+            let lagrange_openings_commitments = run_with_threads(|| {
+                super::lagrange_openings_commitments_at_zero::<G1Projective>(tau, n).unwrap()
+            });
+            assert_eq!(lagrange_openings_commitments, openings);
+        }
+    }
 }
@@ -1,15 +1,70 @@
+mod circuit_keys;
+mod columns;
 mod common;
+mod error;
+mod estimate;
+mod evm;
+mod fingerprint;
 mod fk;
+mod import;
+mod inspect;
 mod lagrange;
+mod mmap;
 mod powers;
 mod quotients;
+mod report;
+mod serialization;
+mod setup;
+mod setup_file;
+mod solidity;
 mod srs;
 mod table;
+mod table_file;
+mod tables;
+mod union;
+mod update;
 mod utils;
+mod verify;
+mod vk;
 
+pub use circuit_keys::{circuit_key_dir, circuit_keys, circuit_keys_from_srs, CircuitKey};
+pub use columns::{MultiColumnQs, MultiColumnTable};
+pub use common::CommonPreprocessedInput;
+pub use error::SetupError;
+pub use estimate::{
+    available_memory_bytes, estimate_setup, stage_log_size, Calibration, SetupEstimate,
+    StageEstimate,
+};
+pub use evm::{
+    abi_decode, abi_encode, g1_to_evm, g2_to_evm, setup_file_from_json, setup_file_to_json,
+    EvmEncode, EvmError, G2Order, JsonFormat, NumberFormat,
+};
+pub use fingerprint::TableFingerprint;
+pub use fk::UpperToeplitz;
+pub use import::{
+    import_table, pad_table, parse_table_values, ImportError, Overflow, Padding, TableFormat,
+};
+pub use inspect::{diff_artifacts, Artifact, ArtifactDiff, ArtifactHeader, Elements};
 pub use lagrange::{lagrange_commitments, lagrange_openings_commitments_at_zero};
+pub use mmap::MappedSection;
 pub use powers::compute_tau_powers;
 pub use quotients::compute_qs;
+pub use report::{reports_to_csv, reports_to_json, ResourceUsage, SetupReport};
+pub use serialization::{
+    deserialize_vec, deserialize_vec_from_reader, deserialize_vec_from_slice,
+    deserialize_vec_with_mode, serialize_vec, serialize_vec_with_encoding, Encoding, Validation,
+};
+pub use setup::{setup, SetupOutput, Stage, StageTiming};
+pub use setup_file::{Section, SetupFile, SetupFileError, SetupHeader, SETUP_MAGIC};
+pub use solidity::{keccak256, solidity_verifier, EvmTranscript};
 pub use srs::compute_g_powers;
-pub use table::gen_table;
-pub use utils::{deserialize_vec, serialize_vec, write_bytes};
+pub use table::{gen_table, interpolate_table};
+pub use table_file::{TableFile, TableFileError, TableForm, TableHeader, LEGACY_SPEC, TABLE_MAGIC};
+pub use tables::{BitwiseOp, TableSpec};
+pub use union::{union_qs, TableUnion, UnionEntry, TAG_SHIFT_BITS};
+pub use update::{update_qs, update_qs_with_srs, TableCommitments};
+pub use utils::write_bytes;
+pub use verify::{
+    verify_lagrange_commitments, verify_openings_at_zero, verify_powers, verify_qs, verify_vk,
+};
+pub use vk::VerifierKey;
@@ -0,0 +1,213 @@
+use crate::error::SetupError;
+use crate::fingerprint::TableFingerprint;
+use crate::inspect::Elements;
+use crate::serialization::{Encoding, Validation};
+use crate::setup_file::{Section, SetupFileError, SetupHeader};
+use ark_serialize::CanonicalDeserialize;
+use memmap2::Mmap;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::fs::File;
+use std::marker::PhantomData;
+use std::ops::Range;
+
+/// Uncompressed setup file mapped into memory. Opening only parses the header, every element
+/// is read, and checked unless trusted, when it is accessed, so a prover pays for the points
+/// it touches rather than for the whole section.
+pub struct MappedSection<T> {
+    pub header: SetupHeader,
+    pub validation: Validation,
+    mmap: Mmap,
+    /// start of the serialized `Vec<T>` in the file
+    data_offset: usize,
+    _element: PhantomData<T>,
+}
+
+impl<T: CanonicalDeserialize + Send> MappedSection<T> {
+    /// The file must not be modified while it is mapped
+    pub fn open(path: &str, curve: &str, section: Section) -> Result<Self, SetupError> {
+        Self::open_with_validation(path, curve, section, Validation::Validated)
+    }
+
+    /// Like `open`, and rejects the file unless it was computed for the table with `fingerprint`
+    pub fn open_for_table(
+        path: &str,
+        curve: &str,
+        section: Section,
+        fingerprint: &TableFingerprint,
+    ) -> Result<Self, SetupError> {
+        let mapped = Self::open(path, curve, section)?;
+        mapped.header.expect_table(fingerprint)?;
+        Ok(mapped)
+    }
+
+    pub fn open_with_validation(
+        path: &str,
+        curve: &str,
+        section: Section,
+        validation: Validation,
+    ) -> Result<Self, SetupError> {
+        let file = File::open(path).map_err(SetupFileError::from)?;
+        // safety: the mapping is read-only, and setup files are written once and not modified
+        let mmap = unsafe { Mmap::map(&file) }.map_err(SetupFileError::from)?;
+
+        let (header, data_bytes) = SetupHeader::parse(&mmap)?;
+        header.expect(curve, section)?;
+        if header.encoding != Encoding::Uncompressed {
+            return Err(SetupFileError::NotMappable.into());
+        }
+        let len = Elements::parse(data_bytes, header.encoding)?.len();
+        if len as u64 != header.len {
+            return Err(SetupFileError::LengthMismatch {
+                len: header.len,
+                found: len,
+            }
+            .into());
+        }
+
+        let data_offset = mmap.len() - data_bytes.len();
+        Ok(Self {
+            header,
+            validation,
+            mmap,
+            data_offset,
+            _element: PhantomData,
+        })
+    }
+
+    fn elements(&self) -> Elements<'_> {
+        // checked in `open`
+        Elements::parse(&self.mmap[self.data_offset..], Encoding::Uncompressed).unwrap()
+    }
+
+    pub fn len(&self) -> usize {
+        self.header.len as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, i: usize) -> Result<T, SetupError> {
+        if i >= self.len() {
+            return Err(SetupError::IndexOutOfRange {
+                index: i,
+                len: self.len(),
+            });
+        }
+        self.elements().get_with_validation(i, self.validation)
+    }
+
+    pub fn get_range(&self, range: Range<usize>) -> Result<Vec<T>, SetupError> {
+        if range.end > self.len() || range.start > range.end {
+            return Err(SetupError::IndexOutOfRange {
+                index: range.end,
+                len: self.len(),
+            });
+        }
+        let (elements, validation) = (self.elements(), self.validation);
+
+        #[cfg(not(feature = "parallel"))]
+        let points = range
+            .map(|i| elements.get_with_validation(i, validation))
+            .collect();
+
+        #[cfg(feature = "parallel")]
+        let points = range
+            .into_par_iter()
+            .map(|i| elements.get_with_validation(i, validation))
+            .collect();
+
+        points
+    }
+
+    /// Hashes the whole section, which reads every page of the file
+    pub fn check_hash(&self) -> Result<(), SetupError> {
+        if self.header.content_hash(&self.mmap[self.data_offset..]) != self.header.hash {
+            return Err(SetupFileError::HashMismatch.into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod mmap_test {
+    use super::MappedSection;
+    use crate::{
+        compute_g_powers, gen_table, Encoding, Section, SetupError, SetupFile, SetupFileError,
+        TableFingerprint, Validation,
+    };
+    use ark_bn254::{Fr, G1Affine, G1Projective};
+
+    #[test]
+    fn test_mapped_section() {
+        let dir = std::env::temp_dir().join(format!("cq_mmap_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("g1_powers.bin").to_string_lossy().into_owned();
+        let compressed_path = dir.join("compressed.bin").to_string_lossy().into_owned();
+
+        let powers = compute_g_powers::<G1Projective>(Fr::from(100u64), 16);
+        SetupFile::with_encoding(
+            "bn254",
+            Section::G1Powers,
+            Encoding::Uncompressed,
+            powers.clone(),
+        )
+        .write(&path)
+        .unwrap();
+        SetupFile::new("bn254", Section::G1Powers, powers.clone())
+            .write(&compressed_path)
+            .unwrap();
+
+        let mapped = MappedSection::<G1Affine>::open(&path, "bn254", Section::G1Powers).unwrap();
+        assert_eq!(mapped.len(), 16);
+        assert_eq!(mapped.get(11).unwrap(), powers[11]);
+        assert_eq!(mapped.get_range(3..9).unwrap(), powers[3..9]);
+        mapped.check_hash().unwrap();
+        let trusted = MappedSection::<G1Affine>::open_with_validation(
+            &path,
+            "bn254",
+            Section::G1Powers,
+            Validation::Trusted,
+        )
+        .unwrap();
+        assert_eq!(trusted.get_range(0..16).unwrap(), powers);
+        assert!(matches!(
+            mapped.get(16),
+            Err(SetupError::IndexOutOfRange { index: 16, len: 16 })
+        ));
+
+        assert!(matches!(
+            MappedSection::<G1Affine>::open(&path, "bn254", Section::Qs),
+            Err(SetupError::SetupFile(
+                SetupFileError::SectionMismatch { .. }
+            ))
+        ));
+        assert!(matches!(
+            MappedSection::<G1Affine>::open(&compressed_path, "bn254", Section::G1Powers),
+            Err(SetupError::SetupFile(SetupFileError::NotMappable))
+        ));
+
+        let t = gen_table::<Fr>(3, Some("mapped")).unwrap();
+        let fingerprint = TableFingerprint::from_poly("bn254", &t, 8).unwrap();
+        let bound_path = dir.join("bound.bin").to_string_lossy().into_owned();
+        SetupFile::with_encoding("bn254", Section::Qs, Encoding::Uncompressed, powers)
+            .for_table(fingerprint)
+            .write(&bound_path)
+            .unwrap();
+        MappedSection::<G1Affine>::open_for_table(&bound_path, "bn254", Section::Qs, &fingerprint)
+            .unwrap()
+            .check_hash()
+            .unwrap();
+        assert!(matches!(
+            MappedSection::<G1Affine>::open_for_table(
+                &path,
+                "bn254",
+                Section::G1Powers,
+                &fingerprint
+            ),
+            Err(SetupError::TableMismatch { found: None, .. })
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,34 +1,26 @@
 #[cfg(feature = "parallel")]
 use crate::utils::parallelize;
 use ark_ff::FftField;
-#[cfg(feature = "parallel")]
-use rayon::{self, prelude::*};
 
 /// [tau^0, ..., tau^{n-1}]
 pub fn compute_tau_powers<F: FftField>(tau: F, n: usize) -> Vec<F> {
-    let mut t_pows = vec![F::zero(); n - 1];
-
     #[cfg(not(feature = "parallel"))]
-    let t_pows: Vec<F> = std::iter::once(F::one())
-        .chain(t_pows.iter().scan(F::one(), |state, _| {
-            *state *= &tau;
-            Some(*state)
-        }))
+    let t_pows: Vec<F> = std::iter::successors(Some(F::one()), |state| Some(*state * tau))
+        .take(n)
         .collect();
 
     #[cfg(feature = "parallel")]
-    {
-        use ark_ff::Field;
-        use ark_ff::Zero;
-        t_pows.push(F::one());
+    let t_pows: Vec<F> = {
+        let mut t_pows = vec![F::zero(); n];
         parallelize(&mut t_pows, |tau_chunk, start| {
-            let mut current_tau: F = tau.pow(&[start as u64]);
+            let mut current_tau: F = tau.pow([start as u64]);
             for tau_i in tau_chunk.iter_mut() {
                 *tau_i = current_tau;
                 current_tau *= tau;
             }
         });
-    }
+        t_pows
+    };
 
     t_pows
 }
@@ -42,7 +34,7 @@ mod powers_test {
     fn test_tau_pows() {
         use ark_bn254::Fr;
         let n = 5;
-        let tau = Fr::from(2 as u64);
+        let tau = Fr::from(2u64);
 
         let tau_pows = super::compute_tau_powers::<Fr>(tau, n);
         let tau_successors: Vec<Fr> = std::iter::successors(Some(Fr::one()), |p| Some(*p * tau))
@@ -51,4 +43,31 @@ mod powers_test {
 
         assert_eq!(tau_pows, tau_successors);
     }
+
+    // cargo test --features=parallel test_tau_pows_empty
+    #[test]
+    fn test_tau_pows_empty() {
+        use ark_bn254::Fr;
+        assert!(super::compute_tau_powers::<Fr>(Fr::from(2u64), 0).is_empty());
+    }
+
+    // cargo test --features=parallel consistency_tau_powers
+    #[test]
+    fn consistency_tau_powers() {
+        use crate::utils::run_with_threads;
+        use ark_bn254::Fr;
+        use ark_ff::{Field, UniformRand};
+
+        let mut rng = ark_std::test_rng();
+        for k in 0..=6 {
+            let n = 1 << k;
+            let tau = Fr::rand(&mut rng);
+
+            let naive: Vec<Fr> = (0..n).map(|i| tau.pow([i as u64])).collect();
+            assert_eq!(
+                run_with_threads(|| super::compute_tau_powers(tau, n)),
+                naive
+            );
+        }
+    }
 }
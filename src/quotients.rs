@@ -1,47 +1,77 @@
+use crate::error::SetupError;
 use crate::fk::UpperToeplitz;
+#[cfg(feature = "parallel")]
+use crate::utils::parallelize;
 use ark_ec::{pairing::Pairing, CurveGroup, Group};
 use ark_ff::Field;
 use ark_poly::{univariate::DensePolynomial, EvaluationDomain, GeneralEvaluationDomain};
 use std::ops::Mul;
 
+/// qs of table `t` over `domain`, the table size is `domain.size()` whatever the degree of `t`.
+/// `tau_powers` may come from a larger SRS, only the first `domain.size()` powers are used.
 pub fn compute_qs<E: Pairing>(
     t: &DensePolynomial<E::ScalarField>,
     domain: &GeneralEvaluationDomain<E::ScalarField>,
     tau_powers: &[E::ScalarField],
-) -> Vec<E::G1Affine> {
+) -> Result<Vec<E::G1Affine>, SetupError> {
     /*
         - N (table size) is always pow2
         - Toeplitz multiplication will happen in 2 * N, so appending zero commitments on hs is not needed
     */
+    if tau_powers.len() < domain.size() {
+        return Err(SetupError::SizeMismatch {
+            expected: domain.size(),
+            found: tau_powers.len(),
+        });
+    }
 
-    let toeplitz = UpperToeplitz::from_poly(t);
+    let toeplitz = UpperToeplitz::from_poly_with_size(t, domain.size())?;
 
-    let mut tau_rev = tau_powers.to_vec();
+    let mut tau_rev = tau_powers[..domain.size()].to_vec();
     tau_rev.reverse();
 
-    let hs: Vec<E::ScalarField> = toeplitz.mul_by_vec(&tau_rev);
+    let hs: Vec<E::ScalarField> = toeplitz.mul_by_vec(&tau_rev)?;
     assert_eq!(hs.len(), 2 * domain.size());
 
     let ks: Vec<_> = domain.fft(&hs[..domain.size()]);
 
     let n_inv = domain.size_as_field_element().inverse().unwrap();
-    let normalized_roots = domain.elements().map(|g_i| g_i * n_inv);
 
     let gen = E::G1::generator();
-    let qs_at_tau: Vec<E::G1> = ks
-        .iter()
-        .zip(normalized_roots)
-        .map(|(&ki, normalizer_i)| gen.mul(ki * normalizer_i))
-        .collect();
 
-    E::G1::normalize_batch(&qs_at_tau)
+    #[cfg(not(feature = "parallel"))]
+    let qs_at_tau: Vec<E::G1> = {
+        let normalized_roots = domain.elements().map(|g_i| g_i * n_inv);
+        ks.iter()
+            .zip(normalized_roots)
+            .map(|(&ki, normalizer_i)| gen.mul(ki * normalizer_i))
+            .collect()
+    };
+
+    #[cfg(feature = "parallel")]
+    let qs_at_tau: Vec<E::G1> = {
+        use ark_ff::Zero;
+        let w = domain.group_gen();
+        let mut qs_at_tau = vec![E::G1::zero(); domain.size()];
+        parallelize(&mut qs_at_tau, |qs, start| {
+            let mut normalizer_i = domain.element(start) * n_inv;
+            for (qi, &ki) in qs.iter_mut().zip(&ks[start..]) {
+                *qi = gen.mul(ki * normalizer_i);
+                normalizer_i *= w;
+            }
+        });
+        qs_at_tau
+    };
+
+    Ok(E::G1::normalize_batch(&qs_at_tau))
 }
 
 #[cfg(test)]
 mod powers_test {
-    use ark_bn254::{Bn254, Fr, G1Projective};
-    use ark_ec::Group;
-    use ark_ff::{Field, One};
+    use crate::SetupError;
+    use ark_bn254::{Bn254, Fr, G1Affine, G1Projective};
+    use ark_ec::{AffineRepr, CurveGroup, Group};
+    use ark_ff::{Field, One, Zero};
     use ark_poly::{
         univariate::DensePolynomial, DenseUVPolynomial, EvaluationDomain, GeneralEvaluationDomain,
         Polynomial,
@@ -49,44 +79,122 @@ mod powers_test {
     use ark_std::{test_rng, UniformRand};
     use std::ops::Mul;
 
-    // cargo test test_qs
-    #[test]
-    fn test_qs() {
-        let k = 5;
-        let n = 1 << k;
-        let tau = Fr::from(100 as u64);
-
-        let domain = GeneralEvaluationDomain::<Fr>::new(n).unwrap();
+    fn tau_powers(tau: Fr, n: usize) -> Vec<Fr> {
+        std::iter::successors(Some(Fr::one()), |p| Some(*p * tau))
+            .take(n)
+            .collect()
+    }
 
+    // w^i / N * (T(tau) - t_i) / (tau - w^i) by polynomial division
+    fn naive_qs(domain: &GeneralEvaluationDomain<Fr>, t_evals: &[Fr], tau: Fr) -> Vec<G1Affine> {
         let n_inv = domain.size_as_field_element().inverse().unwrap();
-        let normalized_roots: Vec<_> = domain.elements().map(|g_i| g_i * n_inv).collect();
-
-        let mut rng = test_rng();
-
-        let t_evals: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
-        let t_poly = DensePolynomial::from_coefficients_slice(&domain.ifft(&t_evals));
+        let t_poly = DensePolynomial::from_coefficients_slice(&domain.ifft(t_evals));
 
         let g: G1Projective = G1Projective::generator();
-        let q_commitments: Vec<G1Projective> = (0..n)
+        let q_commitments: Vec<G1Projective> = (0..domain.size())
             .map(|i| {
                 let x_minus_w_i =
                     DensePolynomial::from_coefficients_slice(&[-domain.element(i), Fr::one()]);
 
-                let mut t_poly_i = t_poly.clone();
-                t_poly_i[0] -= t_evals[i];
+                let t_poly_i = &t_poly - &DensePolynomial::from_coefficients_slice(&[t_evals[i]]);
 
                 let q = &t_poly_i / &x_minus_w_i;
                 assert_eq!(t_poly_i, &q * &x_minus_w_i);
 
-                g.mul(q.evaluate(&tau) * normalized_roots[i])
+                g.mul(q.evaluate(&tau) * domain.element(i) * n_inv)
             })
             .collect();
+        G1Projective::normalize_batch(&q_commitments)
+    }
 
-        let tau_powers: Vec<Fr> = std::iter::successors(Some(Fr::one()), |p| Some(*p * tau))
-            .take(n)
-            .collect();
+    fn qs(
+        domain: &GeneralEvaluationDomain<Fr>,
+        t_evals: &[Fr],
+        tau_powers: &[Fr],
+    ) -> Vec<G1Affine> {
+        let t_poly = DensePolynomial::from_coefficients_slice(&domain.ifft(t_evals));
+        super::compute_qs::<Bn254>(&t_poly, domain, tau_powers).unwrap()
+    }
 
-        let qs = super::compute_qs::<Bn254>(&t_poly, &domain, &tau_powers);
-        assert_eq!(qs, q_commitments);
+    // cargo test test_qs
+    #[test]
+    fn test_qs() {
+        let k = 5;
+        let n = 1 << k;
+        let tau = Fr::from(100u64);
+
+        let domain = GeneralEvaluationDomain::<Fr>::new(n).unwrap();
+
+        let mut rng = test_rng();
+        let t_evals: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let q_commitments = naive_qs(&domain, &t_evals, tau);
+
+        assert_eq!(qs(&domain, &t_evals, &tau_powers(tau, n)), q_commitments);
+
+        // powers of a 4 times larger SRS are truncated to the table size
+        assert_eq!(
+            qs(&domain, &t_evals, &tau_powers(tau, 4 * n)),
+            q_commitments
+        );
+
+        let t_poly = DensePolynomial::from_coefficients_slice(&domain.ifft(&t_evals));
+        assert!(matches!(
+            super::compute_qs::<Bn254>(&t_poly, &domain, &tau_powers(tau, n - 1)),
+            Err(SetupError::SizeMismatch {
+                expected: 32,
+                found: 31
+            })
+        ));
+    }
+
+    // cargo test test_qs_degenerate_tables
+    #[test]
+    fn test_qs_degenerate_tables() {
+        let tau = Fr::from(100u64);
+
+        for k in 0..4 {
+            let n = 1 << k;
+            let domain = GeneralEvaluationDomain::<Fr>::new(n).unwrap();
+            let tau_powers = tau_powers(tau, n);
+
+            // all zero and constant tables interpolate to polynomials of degree at most 0,
+            // T(X) - t_i is zero so every quotient is the identity
+            for t_evals in [vec![Fr::zero(); n], vec![Fr::from(7u64); n]] {
+                let qs = qs(&domain, &t_evals, &tau_powers);
+                assert_eq!(qs, naive_qs(&domain, &t_evals, tau));
+                assert!(qs.iter().all(|q| q.is_zero()));
+            }
+
+            // entries of 3 + 5X have degree 1, far below N - 1
+            let linear: Vec<Fr> = domain
+                .elements()
+                .map(|w_i| Fr::from(3u64) + Fr::from(5u64) * w_i)
+                .collect();
+            assert_eq!(
+                qs(&domain, &linear, &tau_powers),
+                naive_qs(&domain, &linear, tau)
+            );
+        }
+    }
+
+    // cargo test --features=parallel consistency_qs
+    #[test]
+    fn consistency_qs() {
+        use crate::{gen_table, utils::run_with_threads};
+
+        let mut rng = test_rng();
+        for k in 0..=6 {
+            let n = 1 << k;
+            let tau = Fr::rand(&mut rng);
+            let domain = GeneralEvaluationDomain::<Fr>::new(n).unwrap();
+
+            let t = gen_table::<Fr>(k, Some(&format!("consistency-{}", k))).unwrap();
+            let t_evals = domain.fft(&t.coeffs);
+            let tau_powers = tau_powers(tau, n);
+
+            let qs =
+                run_with_threads(|| super::compute_qs::<Bn254>(&t, &domain, &tau_powers).unwrap());
+            assert_eq!(qs, naive_qs(&domain, &t_evals, tau));
+        }
     }
 }
@@ -0,0 +1,253 @@
+use crate::setup::StageTiming;
+use serde_json::{json, Value};
+use std::fmt::Write;
+use std::time::Duration;
+
+/// Cpu time and peak resident memory of the whole process so far
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ResourceUsage {
+    pub cpu_time: Duration,
+    pub peak_rss_bytes: u64,
+}
+
+impl ResourceUsage {
+    #[cfg(unix)]
+    pub fn current() -> Self {
+        let mut usage = std::mem::MaybeUninit::<libc::rusage>::uninit();
+        // SAFETY: getrusage only writes into the provided struct
+        let usage = unsafe {
+            if libc::getrusage(libc::RUSAGE_SELF, usage.as_mut_ptr()) != 0 {
+                return Self::default();
+            }
+            usage.assume_init()
+        };
+
+        let to_duration = |t: libc::timeval| {
+            Duration::from_secs(t.tv_sec as u64) + Duration::from_micros(t.tv_usec as u64)
+        };
+
+        // linux reports ru_maxrss in kilobytes, macos in bytes
+        #[cfg(target_os = "macos")]
+        let peak_rss_bytes = usage.ru_maxrss as u64;
+        #[cfg(not(target_os = "macos"))]
+        let peak_rss_bytes = usage.ru_maxrss as u64 * 1024;
+
+        Self {
+            cpu_time: to_duration(usage.ru_utime) + to_duration(usage.ru_stime),
+            peak_rss_bytes,
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub fn current() -> Self {
+        Self::default()
+    }
+}
+
+/// Features the library was built with
+pub fn build_features() -> Vec<&'static str> {
+    let mut features = vec![];
+    if cfg!(feature = "parallel") {
+        features.push("parallel");
+    }
+    features
+}
+
+/// Number of threads the setup runs on
+pub fn num_threads() -> usize {
+    #[cfg(feature = "parallel")]
+    let num_threads = rayon::current_num_threads();
+
+    #[cfg(not(feature = "parallel"))]
+    let num_threads = 1;
+
+    num_threads
+}
+
+/// Machine readable summary of a single setup run
+pub struct SetupReport {
+    pub curve: String,
+    pub table_k: usize,
+    pub circuit_k: usize,
+    pub threads: usize,
+    pub features: Vec<&'static str>,
+    pub wall_time: Duration,
+    pub cpu_time: Duration,
+    pub peak_rss_bytes: u64,
+    pub stages: Vec<StageTiming>,
+}
+
+fn millis(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+/// Milliseconds to microsecond precision, as the csv writes them
+fn millis_rounded(d: Duration) -> f64 {
+    (millis(d) * 1000.0).round() / 1000.0
+}
+
+impl SetupReport {
+    /// `usage_before` is the resource usage sampled right before the run started
+    pub fn new(
+        curve: &str,
+        table_k: usize,
+        circuit_k: usize,
+        wall_time: Duration,
+        usage_before: ResourceUsage,
+        stages: Vec<StageTiming>,
+    ) -> Self {
+        let usage = ResourceUsage::current();
+        Self {
+            curve: curve.to_string(),
+            table_k,
+            circuit_k,
+            threads: num_threads(),
+            features: build_features(),
+            wall_time,
+            cpu_time: usage.cpu_time.saturating_sub(usage_before.cpu_time),
+            peak_rss_bytes: usage.peak_rss_bytes,
+            stages,
+        }
+    }
+
+    pub fn to_json_value(&self) -> Value {
+        let stages: Vec<Value> = self
+            .stages
+            .iter()
+            .map(|timing| {
+                json!({
+                    "stage": timing.stage.to_string(),
+                    "wall_ms": millis_rounded(timing.wall_time),
+                    "cpu_ms": millis_rounded(timing.cpu_time),
+                    "peak_rss_bytes": timing.peak_rss_bytes,
+                })
+            })
+            .collect();
+        json!({
+            "curve": self.curve,
+            "table_k": self.table_k,
+            "circuit_k": self.circuit_k,
+            "threads": self.threads,
+            "features": self.features,
+            "wall_ms": millis_rounded(self.wall_time),
+            "cpu_ms": millis_rounded(self.cpu_time),
+            "peak_rss_bytes": self.peak_rss_bytes,
+            "stages": stages,
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.to_json_value()).unwrap()
+    }
+
+    pub const CSV_HEADER: &'static str =
+        "curve,table_k,circuit_k,threads,features,stage,wall_ms,cpu_ms,peak_rss_bytes";
+
+    /// One row per stage followed by a `total` row
+    pub fn to_csv_rows(&self) -> String {
+        let prefix = format!(
+            "{},{},{},{},{}",
+            self.curve,
+            self.table_k,
+            self.circuit_k,
+            self.threads,
+            self.features.join("+")
+        );
+        let mut csv = String::new();
+        for timing in self.stages.iter() {
+            writeln!(
+                csv,
+                "{},{},{:.3},{:.3},{}",
+                prefix,
+                timing.stage,
+                millis(timing.wall_time),
+                millis(timing.cpu_time),
+                timing.peak_rss_bytes
+            )
+            .unwrap();
+        }
+        writeln!(
+            csv,
+            "{},total,{:.3},{:.3},{}",
+            prefix,
+            millis(self.wall_time),
+            millis(self.cpu_time),
+            self.peak_rss_bytes
+        )
+        .unwrap();
+        csv
+    }
+}
+
+/// Reports of several runs as a json array
+pub fn reports_to_json(reports: &[SetupReport]) -> String {
+    let reports: Vec<Value> = reports.iter().map(SetupReport::to_json_value).collect();
+    serde_json::to_string_pretty(&reports).unwrap()
+}
+
+/// Reports of several runs as a single csv table
+pub fn reports_to_csv(reports: &[SetupReport]) -> String {
+    let mut csv = format!("{}\n", SetupReport::CSV_HEADER);
+    for report in reports {
+        csv.push_str(&report.to_csv_rows());
+    }
+    csv
+}
+
+#[cfg(test)]
+mod report_test {
+    use super::{reports_to_csv, reports_to_json, ResourceUsage, SetupReport};
+    use crate::setup::{Stage, StageTiming};
+    use serde_json::{json, Value};
+    use std::time::Duration;
+
+    fn report() -> SetupReport {
+        let stages = Stage::ALL
+            .iter()
+            .map(|&stage| StageTiming {
+                stage,
+                wall_time: Duration::from_micros(1500),
+                cpu_time: Duration::from_millis(3),
+                peak_rss_bytes: 1024,
+            })
+            .collect();
+        SetupReport::new(
+            "bn254",
+            4,
+            3,
+            Duration::from_millis(10),
+            ResourceUsage::current(),
+            stages,
+        )
+    }
+
+    #[test]
+    fn test_report_formats() {
+        let report = report();
+
+        let json: Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(json["curve"], "bn254");
+        assert_eq!(json["circuit_k"], 3);
+        assert!(json["stages"].as_array().unwrap().contains(&json!({
+            "stage": "compute_qs",
+            "wall_ms": 1.5,
+            "cpu_ms": 3.0,
+            "peak_rss_bytes": 1024
+        })));
+
+        let csv = reports_to_csv(&[report]);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], SetupReport::CSV_HEADER);
+        assert_eq!(lines.len(), Stage::ALL.len() + 2);
+        assert!(lines[1].ends_with("compute_tau_powers,1.500,3.000,1024"));
+        assert!(lines.last().unwrap().contains(",total,10.000,"));
+    }
+
+    #[test]
+    fn test_report_json_escapes() {
+        let mut report = report();
+        report.curve = "bn\"254".to_string();
+        let json: Value = serde_json::from_str(&reports_to_json(&[report])).unwrap();
+        assert_eq!(json[0]["curve"], "bn\"254");
+    }
+}
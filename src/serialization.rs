@@ -0,0 +1,185 @@
+use crate::error::SetupError;
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Validate,
+};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::fs::File;
+
+/// How points are serialized
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    /// smallest files, every point is decompressed when read
+    Compressed,
+    /// twice the size, but fixed stride and no square roots when read
+    Uncompressed,
+}
+
+impl Encoding {
+    pub fn compress(self) -> Compress {
+        match self {
+            Encoding::Compressed => Compress::Yes,
+            Encoding::Uncompressed => Compress::No,
+        }
+    }
+}
+
+/// Whether points are checked to be on the curve and in the prime order subgroup when read
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Validation {
+    Validated,
+    /// only for data from a trusted source, invalid points are silently accepted
+    Trusted,
+}
+
+impl Validation {
+    pub fn validate(self) -> Validate {
+        match self {
+            Validation::Validated => Validate::Yes,
+            Validation::Trusted => Validate::No,
+        }
+    }
+}
+
+pub fn serialize_vec<T: CanonicalSerialize>(x: &[T]) -> Vec<u8> {
+    serialize_vec_with_encoding(x, Encoding::Compressed)
+}
+
+pub fn serialize_vec_with_encoding<T: CanonicalSerialize>(x: &[T], encoding: Encoding) -> Vec<u8> {
+    let mut data = Vec::<u8>::new();
+    x.serialize_with_mode(&mut data, encoding.compress())
+        .unwrap();
+    data
+}
+
+/*
+    A serialized Vec<T> is a u64 length followed by the elements. Points and field elements
+    have a fixed size in either encoding, so the size of the first element gives the offset of
+    every other one, and the elements are decompressed and checked independently.
+*/
+
+/// Deserializes a `Vec<T>` of fixed size elements, in parallel under the `parallel` feature.
+/// Bytes after the last element are ignored.
+pub fn deserialize_vec_from_slice<T: CanonicalDeserialize + Send>(
+    bytes: &[u8],
+    encoding: Encoding,
+    validation: Validation,
+) -> Result<Vec<T>, SerializationError> {
+    let (compress, validate) = (encoding.compress(), validation.validate());
+    let mut reader = bytes;
+    let len = u64::deserialize_compressed(&mut reader)?;
+    if len == 0 {
+        return Ok(vec![]);
+    }
+
+    let mut first = reader;
+    T::deserialize_with_mode(&mut first, compress, Validate::No)?;
+    let stride = reader.len() - first.len();
+    let data = usize::try_from(len)
+        .ok()
+        .and_then(|len| len.checked_mul(stride))
+        .and_then(|size| reader.get(..size))
+        .ok_or(SerializationError::NotEnoughSpace)?;
+    if stride == 0 {
+        return Err(SerializationError::InvalidData);
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    let elements = data
+        .chunks(stride)
+        .map(|chunk| T::deserialize_with_mode(chunk, compress, validate))
+        .collect();
+
+    #[cfg(feature = "parallel")]
+    let elements = data
+        .par_chunks(stride)
+        .map(|chunk| T::deserialize_with_mode(chunk, compress, validate))
+        .collect();
+
+    elements
+}
+
+/// Reads the stream to its end, then deserializes it like `deserialize_vec_from_slice`
+pub fn deserialize_vec_from_reader<T: CanonicalDeserialize + Send, R: Read>(
+    mut reader: R,
+    encoding: Encoding,
+    validation: Validation,
+) -> Result<Vec<T>, SetupError> {
+    let mut buffer = Vec::new();
+    reader.read_to_end(&mut buffer)?;
+    Ok(deserialize_vec_from_slice(&buffer, encoding, validation)?)
+}
+
+/// Reads a file written by `serialize_vec`
+pub fn deserialize_vec<T: CanonicalDeserialize + Send>(path: &str) -> Result<Vec<T>, SetupError> {
+    deserialize_vec_with_mode(path, Encoding::Compressed, Validation::Validated)
+}
+
+pub fn deserialize_vec_with_mode<T: CanonicalDeserialize + Send>(
+    path: &str,
+    encoding: Encoding,
+    validation: Validation,
+) -> Result<Vec<T>, SetupError> {
+    deserialize_vec_from_reader(File::open(path)?, encoding, validation)
+}
+
+#[cfg(test)]
+mod serialization_test {
+    use super::{
+        deserialize_vec_from_reader, deserialize_vec_from_slice, serialize_vec_with_encoding,
+        Encoding, Validation,
+    };
+    use crate::compute_g_powers;
+    use ark_bn254::{Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+
+    #[test]
+    fn test_modes() {
+        let tau = Fr::from(100u64);
+        let g1 = compute_g_powers::<G1Projective>(tau, 9);
+        let g2 = compute_g_powers::<G2Projective>(tau, 5);
+        for encoding in [Encoding::Compressed, Encoding::Uncompressed] {
+            for validation in [Validation::Validated, Validation::Trusted] {
+                let bytes = serialize_vec_with_encoding(&g1, encoding);
+                let read: Vec<G1Affine> =
+                    deserialize_vec_from_slice(&bytes, encoding, validation).unwrap();
+                assert_eq!(read, g1);
+
+                let bytes = serialize_vec_with_encoding(&g2, encoding);
+                let read: Vec<G2Affine> =
+                    deserialize_vec_from_reader(bytes.as_slice(), encoding, validation).unwrap();
+                assert_eq!(read, g2);
+            }
+        }
+
+        let empty: Vec<G1Affine> = vec![];
+        let bytes = serialize_vec_with_encoding(&empty, Encoding::Compressed);
+        let read: Vec<G1Affine> =
+            deserialize_vec_from_slice(&bytes, Encoding::Compressed, Validation::Validated)
+                .unwrap();
+        assert!(read.is_empty());
+
+        // moves the last point off the curve, only trusted mode accepts it
+        let mut bytes = serialize_vec_with_encoding(&g1, Encoding::Uncompressed);
+        let last_x = bytes.len() - 64;
+        bytes[last_x] ^= 1;
+        assert!(deserialize_vec_from_slice::<G1Affine>(
+            &bytes,
+            Encoding::Uncompressed,
+            Validation::Validated
+        )
+        .is_err());
+        let read: Vec<G1Affine> =
+            deserialize_vec_from_slice(&bytes, Encoding::Uncompressed, Validation::Trusted)
+                .unwrap();
+        assert!(!read[8].is_on_curve());
+        assert_eq!(read[..8], g1[..8]);
+
+        let truncated = &bytes[..bytes.len() - 1];
+        assert!(deserialize_vec_from_slice::<G1Affine>(
+            truncated,
+            Encoding::Uncompressed,
+            Validation::Trusted
+        )
+        .is_err());
+    }
+}
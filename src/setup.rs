@@ -0,0 +1,397 @@
+use crate::error::{check_tau, domain, SetupError};
+use crate::report::ResourceUsage;
+use crate::{
+    compute_g_powers, compute_qs, compute_tau_powers, lagrange_commitments,
+    lagrange_openings_commitments_at_zero,
+};
+use ark_ec::pairing::Pairing;
+use ark_poly::{univariate::DensePolynomial, GeneralEvaluationDomain};
+use std::fmt;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Single step of the setup. Stages only communicate through the shared intermediates,
+/// so every stage whose dependencies are done can run at the same time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Stage {
+    TauPowers,
+    LagrangeCommitments,
+    CircuitLagrangeCommitments,
+    LagrangeOpeningsAtZero,
+    G1Powers,
+    G2Powers,
+    Qs,
+}
+
+impl Stage {
+    /// All stages in topological order
+    pub const ALL: [Stage; 7] = [
+        Stage::TauPowers,
+        Stage::LagrangeCommitments,
+        Stage::CircuitLagrangeCommitments,
+        Stage::LagrangeOpeningsAtZero,
+        Stage::G1Powers,
+        Stage::G2Powers,
+        Stage::Qs,
+    ];
+
+    pub fn dependencies(&self) -> &'static [Stage] {
+        match self {
+            Stage::Qs => &[Stage::TauPowers],
+            _ => &[],
+        }
+    }
+
+    pub fn dependents(&self) -> impl Iterator<Item = Stage> + '_ {
+        Self::ALL
+            .into_iter()
+            .filter(move |stage| stage.dependencies().contains(self))
+    }
+
+    fn index(&self) -> usize {
+        Self::ALL.iter().position(|stage| stage == self).unwrap()
+    }
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Stage::TauPowers => "compute_tau_powers",
+            Stage::LagrangeCommitments => "lagrange_commitments",
+            Stage::CircuitLagrangeCommitments => "lagrange_commitments circuit",
+            Stage::LagrangeOpeningsAtZero => "lagrange_openings_commitments_at_zero",
+            Stage::G1Powers => "compute_g_powers G1",
+            Stage::G2Powers => "compute_g_powers G2",
+            Stage::Qs => "compute_qs",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Resources spent on a single stage. Cpu time is measured for the whole process,
+/// so it also includes the work of stages that were running at the same time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StageTiming {
+    pub stage: Stage,
+    pub wall_time: Duration,
+    pub cpu_time: Duration,
+    pub peak_rss_bytes: u64,
+}
+
+/// Everything produced by `setup`, together with the resources spent on each stage
+pub struct SetupOutput<E: Pairing> {
+    /// N powers of tau
+    pub powers_of_tau: Vec<E::ScalarField>,
+    /// over the table domain of size N
+    pub lagrange_commitments: Vec<E::G1Affine>,
+    /// over the circuit domain of size n
+    pub circuit_lagrange_commitments: Vec<E::G1Affine>,
+    pub lagrange_openings_at_zero: Vec<E::G1Affine>,
+    /// max(N, n) powers
+    pub g1_powers: Vec<E::G1Affine>,
+    /// max(N, n) + 1 powers
+    pub g2_powers: Vec<E::G2Affine>,
+    pub qs: Vec<E::G1Affine>,
+    /// [tau^{max(N, n) - 1 - (n - 2)}]_2, bounds the degree of B0 for circuits of size n
+    pub x_b0_bound: E::G2Affine,
+    pub timings: Vec<StageTiming>,
+}
+
+struct Intermediates<E: Pairing> {
+    domain: GeneralEvaluationDomain<E::ScalarField>,
+    powers_of_tau: OnceLock<Vec<E::ScalarField>>,
+    lagrange_commitments: OnceLock<Vec<E::G1Affine>>,
+    circuit_lagrange_commitments: OnceLock<Vec<E::G1Affine>>,
+    lagrange_openings_at_zero: OnceLock<Vec<E::G1Affine>>,
+    g1_powers: OnceLock<Vec<E::G1Affine>>,
+    g2_powers: OnceLock<Vec<E::G2Affine>>,
+    qs: OnceLock<Vec<E::G1Affine>>,
+    timings: Mutex<Vec<StageTiming>>,
+    /// first error of any stage, the remaining stages still run
+    error: Mutex<Option<SetupError>>,
+}
+
+impl<E: Pairing> Intermediates<E> {
+    fn set<T>(&self, cell: &OnceLock<T>, result: Result<T, SetupError>) {
+        match result {
+            // every stage runs exactly once, so none of the cells can already be set
+            Ok(value) => {
+                let _ = cell.set(value);
+            }
+            Err(err) => {
+                self.error.lock().unwrap().get_or_insert(err);
+            }
+        }
+    }
+}
+
+/// Runs the whole setup for table `t` of size `table_n` and circuits of size `circuit_n`.
+/// The SRS covers both, G2 powers are always 1 longer than G1 powers.
+pub fn setup<E: Pairing>(
+    tau: E::ScalarField,
+    table_n: usize,
+    circuit_n: usize,
+    t: &DensePolynomial<E::ScalarField>,
+) -> Result<SetupOutput<E>, SetupError> {
+    let table_domain = domain(table_n)?;
+    domain::<E::ScalarField>(circuit_n)?;
+    // B0 has degree n - 2
+    if circuit_n < 2 {
+        return Err(SetupError::CircuitTooSmall { size: circuit_n });
+    }
+    if t.coeffs.len() > table_n {
+        return Err(SetupError::SizeMismatch {
+            expected: table_n,
+            found: t.coeffs.len(),
+        });
+    }
+    let srs_len = table_n.max(circuit_n);
+    // both domains are subgroups of the one of size srs_len
+    check_tau(tau, srs_len)?;
+
+    let state = Intermediates::<E> {
+        domain: table_domain,
+        powers_of_tau: OnceLock::new(),
+        lagrange_commitments: OnceLock::new(),
+        circuit_lagrange_commitments: OnceLock::new(),
+        lagrange_openings_at_zero: OnceLock::new(),
+        g1_powers: OnceLock::new(),
+        g2_powers: OnceLock::new(),
+        qs: OnceLock::new(),
+        timings: Mutex::new(Vec::with_capacity(Stage::ALL.len())),
+        error: Mutex::new(None),
+    };
+
+    let run_stage = |stage: Stage| {
+        let now = Instant::now();
+        let usage_before = ResourceUsage::current();
+        match stage {
+            Stage::TauPowers => {
+                state.set(&state.powers_of_tau, Ok(compute_tau_powers(tau, table_n)));
+            }
+            Stage::LagrangeCommitments => {
+                let l_coms = lagrange_commitments::<E::G1>(tau, table_n as u64);
+                state.set(&state.lagrange_commitments, l_coms);
+            }
+            Stage::CircuitLagrangeCommitments => {
+                let l_coms = lagrange_commitments::<E::G1>(tau, circuit_n as u64);
+                state.set(&state.circuit_lagrange_commitments, l_coms);
+            }
+            Stage::LagrangeOpeningsAtZero => {
+                let openings = lagrange_openings_commitments_at_zero::<E::G1>(tau, table_n);
+                state.set(&state.lagrange_openings_at_zero, openings);
+            }
+            Stage::G1Powers => {
+                let g1_powers = compute_g_powers::<E::G1>(tau, srs_len);
+                state.set(&state.g1_powers, Ok(g1_powers));
+            }
+            Stage::G2Powers => {
+                let g2_powers = compute_g_powers::<E::G2>(tau, srs_len + 1);
+                state.set(&state.g2_powers, Ok(g2_powers));
+            }
+            Stage::Qs => {
+                let powers_of_tau = state.powers_of_tau.get().unwrap();
+                let qs = compute_qs::<E>(t, &state.domain, powers_of_tau);
+                state.set(&state.qs, qs);
+            }
+        }
+        let wall_time = now.elapsed();
+        let usage_after = ResourceUsage::current();
+        state.timings.lock().unwrap().push(StageTiming {
+            stage,
+            wall_time,
+            cpu_time: usage_after.cpu_time.saturating_sub(usage_before.cpu_time),
+            peak_rss_bytes: usage_after.peak_rss_bytes,
+        });
+    };
+
+    schedule(&run_stage);
+
+    if let Some(err) = state.error.into_inner().unwrap() {
+        return Err(err);
+    }
+    let mut timings = state.timings.into_inner().unwrap();
+    timings.sort_by_key(|timing| timing.stage.index());
+
+    let g2_powers = state.g2_powers.into_inner().unwrap();
+    let x_b0_bound = g2_powers[srs_len - 1 - (circuit_n - 2)];
+
+    Ok(SetupOutput {
+        powers_of_tau: state.powers_of_tau.into_inner().unwrap(),
+        lagrange_commitments: state.lagrange_commitments.into_inner().unwrap(),
+        circuit_lagrange_commitments: state.circuit_lagrange_commitments.into_inner().unwrap(),
+        lagrange_openings_at_zero: state.lagrange_openings_at_zero.into_inner().unwrap(),
+        g1_powers: state.g1_powers.into_inner().unwrap(),
+        g2_powers,
+        qs: state.qs.into_inner().unwrap(),
+        x_b0_bound,
+        timings,
+    })
+}
+
+/// Runs every stage after all of its dependencies
+#[cfg(not(feature = "parallel"))]
+fn schedule<F: Fn(Stage) + Sync>(run_stage: &F) {
+    for stage in Stage::ALL {
+        run_stage(stage);
+    }
+}
+
+/// Runs every stage after all of its dependencies, a stage is spawned on the rayon pool
+/// as soon as the last of its dependencies finishes
+#[cfg(feature = "parallel")]
+fn schedule<F: Fn(Stage) + Sync>(run_stage: &F) {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn spawn_stage<'s, F: Fn(Stage) + Sync>(
+        scope: &rayon::Scope<'s>,
+        stage: Stage,
+        run_stage: &'s F,
+        pending: &'s [AtomicUsize],
+    ) {
+        scope.spawn(move |scope| {
+            run_stage(stage);
+            for dependent in stage.dependents() {
+                if pending[dependent.index()].fetch_sub(1, Ordering::AcqRel) == 1 {
+                    spawn_stage(scope, dependent, run_stage, pending);
+                }
+            }
+        });
+    }
+
+    let pending: Vec<AtomicUsize> = Stage::ALL
+        .iter()
+        .map(|stage| AtomicUsize::new(stage.dependencies().len()))
+        .collect();
+
+    rayon::scope(|scope| {
+        for stage in Stage::ALL {
+            if stage.dependencies().is_empty() {
+                spawn_stage(scope, stage, run_stage, &pending);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod setup_test {
+    use super::Stage;
+    use crate::{
+        compute_g_powers, compute_qs, compute_tau_powers, gen_table, lagrange_commitments,
+        lagrange_openings_commitments_at_zero, SetupError,
+    };
+    use ark_bn254::{Bn254, Fr, G1Projective, G2Projective};
+    use ark_ec::{CurveGroup, Group};
+    use ark_ff::Field;
+    use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+    use std::ops::Mul;
+
+    #[test]
+    fn stages_are_topologically_ordered() {
+        for (i, stage) in Stage::ALL.iter().enumerate() {
+            for dependency in stage.dependencies() {
+                assert!(Stage::ALL[..i].contains(dependency));
+            }
+        }
+    }
+
+    // cargo test --features=parallel test_setup
+    #[test]
+    fn test_setup() {
+        let k = 4;
+        let n = 1 << k;
+        let tau = Fr::from(100u64);
+        let t = gen_table::<Fr>(k, Some("setup")).unwrap();
+
+        let output = super::setup::<Bn254>(tau, n, n, &t).unwrap();
+
+        let powers_of_tau = compute_tau_powers(tau, n);
+        let domain = GeneralEvaluationDomain::<Fr>::new(n).unwrap();
+        assert_eq!(output.powers_of_tau, powers_of_tau);
+        assert_eq!(
+            output.lagrange_commitments,
+            lagrange_commitments::<G1Projective>(tau, n as u64).unwrap()
+        );
+        assert_eq!(
+            output.circuit_lagrange_commitments,
+            output.lagrange_commitments
+        );
+        assert_eq!(
+            output.lagrange_openings_at_zero,
+            lagrange_openings_commitments_at_zero::<G1Projective>(tau, n).unwrap()
+        );
+        assert_eq!(output.g1_powers, compute_g_powers::<G1Projective>(tau, n));
+        assert_eq!(
+            output.g2_powers,
+            compute_g_powers::<G2Projective>(tau, n + 1)
+        );
+        assert_eq!(
+            output.qs,
+            compute_qs::<Bn254>(&t, &domain, &powers_of_tau).unwrap()
+        );
+
+        let stages: Vec<Stage> = output.timings.iter().map(|timing| timing.stage).collect();
+        assert_eq!(stages, Stage::ALL);
+    }
+
+    #[test]
+    fn test_setup_table_and_circuit_sizes() {
+        let tau = Fr::from(100u64);
+        let g2 = G2Projective::generator();
+
+        // small table, large circuit and the other way round
+        for (table_k, circuit_k) in [(2, 4), (4, 2)] {
+            let (table_n, circuit_n) = (1 << table_k, 1 << circuit_k);
+            let srs_len = std::cmp::max(table_n, circuit_n);
+            let t = gen_table::<Fr>(table_k, Some("sizes")).unwrap();
+
+            let output = super::setup::<Bn254>(tau, table_n, circuit_n, &t).unwrap();
+
+            assert_eq!(output.powers_of_tau.len(), table_n);
+            assert_eq!(output.lagrange_commitments.len(), table_n);
+            assert_eq!(output.lagrange_openings_at_zero.len(), table_n);
+            assert_eq!(output.qs.len(), table_n);
+            assert_eq!(
+                output.circuit_lagrange_commitments,
+                lagrange_commitments::<G1Projective>(tau, circuit_n as u64).unwrap()
+            );
+            assert_eq!(output.g1_powers.len(), srs_len);
+            assert_eq!(output.g2_powers.len(), srs_len + 1);
+            let b0_bound_index = (srs_len - 1 - (circuit_n - 2)) as u64;
+            assert_eq!(
+                output.x_b0_bound,
+                g2.mul(tau.pow([b0_bound_index])).into_affine()
+            );
+        }
+    }
+
+    #[test]
+    fn test_setup_errors() {
+        let t = gen_table::<Fr>(2, Some("errors")).unwrap();
+        let setup =
+            |tau: Fr, table_n, circuit_n| super::setup::<Bn254>(tau, table_n, circuit_n, &t);
+
+        // tau on the 8 element subgroup is degenerate for the table of size 4 as well
+        let w = GeneralEvaluationDomain::<Fr>::new(8).unwrap().group_gen();
+        assert!(matches!(setup(w, 4, 8), Err(SetupError::DegenerateTau)));
+        assert!(matches!(
+            setup(Fr::from(0u64), 4, 4),
+            Err(SetupError::DegenerateTau)
+        ));
+        assert!(matches!(
+            setup(Fr::from(100u64), 6, 4),
+            Err(SetupError::NotPow2 { size: 6 })
+        ));
+        assert!(matches!(
+            setup(Fr::from(100u64), 4, 1),
+            Err(SetupError::CircuitTooSmall { size: 1 })
+        ));
+        assert!(matches!(
+            setup(Fr::from(100u64), 2, 4),
+            Err(SetupError::SizeMismatch {
+                expected: 2,
+                found: 4
+            })
+        ));
+    }
+}
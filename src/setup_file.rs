@@ -0,0 +1,559 @@
+use crate::error::SetupError;
+use crate::fingerprint::TableFingerprint;
+use crate::serialization::{deserialize_vec_from_slice, Encoding, Validation};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError};
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+/*
+    Setup file layout, every header field is written with `serialize_compressed`:
+
+    magic       [u8; 8]   b"CQSETUP\0"
+    version     u8
+    curve       String    e.g. "bn254"
+    section     u8        see `Section`
+    encoding    u8        0 = compressed, 1 = uncompressed
+    table       Option<TableFingerprint>  table the setup was computed for
+    len         u64       number of elements in data
+    hash        [u8; 32]  sha256 of the version, the fields above and the serialized data
+    data        Vec<T>    G1 or G2 points, or a single verifier key, in the given encoding
+
+    Uncompressed points have a fixed size, so element i of an uncompressed file starts at
+    a fixed offset and can be read without touching the rest of the file, see `MappedSection`.
+*/
+
+pub const SETUP_MAGIC: [u8; 8] = *b"CQSETUP\0";
+pub const SETUP_VERSION: u8 = 4;
+
+/// Artifact stored in a setup file
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Section {
+    /// [tau^i]_1 for i < max(N, n)
+    G1Powers,
+    /// [tau^i]_2 for i <= max(N, n)
+    G2Powers,
+    /// [L_i(tau)]_1 over the table domain
+    LagrangeCommitments,
+    /// [L_i(tau)]_1 over the circuit domain
+    CircuitLagrangeCommitments,
+    /// [(L_i(tau) - L_i(0)) / tau]_1 over the table domain
+    LagrangeOpeningsAtZero,
+    Qs,
+    VerifierKey,
+    /// [tau^{max(N, n) - 1 - (n - 2)}]_2, a single point
+    XB0Bound,
+}
+
+impl Section {
+    pub const ALL: [Section; 8] = [
+        Section::G1Powers,
+        Section::G2Powers,
+        Section::LagrangeCommitments,
+        Section::CircuitLagrangeCommitments,
+        Section::LagrangeOpeningsAtZero,
+        Section::Qs,
+        Section::VerifierKey,
+        Section::XB0Bound,
+    ];
+
+    /// Also the default file name of the section, with a `.bin` extension
+    pub fn name(&self) -> &'static str {
+        match self {
+            Section::G1Powers => "g1_powers",
+            Section::G2Powers => "g2_powers",
+            Section::LagrangeCommitments => "lagrange_commitments",
+            Section::CircuitLagrangeCommitments => "circuit_lagrange_commitments",
+            Section::LagrangeOpeningsAtZero => "lagrange_openings_at_zero",
+            Section::Qs => "qs",
+            Section::VerifierKey => "vk",
+            Section::XB0Bound => "x_b0_bound",
+        }
+    }
+
+    /// Inverse of `name`
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|section| section.name() == name)
+    }
+
+    fn to_u8(self) -> u8 {
+        Self::ALL
+            .iter()
+            .position(|section| *section == self)
+            .unwrap() as u8
+    }
+
+    fn from_u8(section: u8) -> Result<Self, SetupFileError> {
+        Self::ALL
+            .get(section as usize)
+            .copied()
+            .ok_or(SetupFileError::UnknownSection(section))
+    }
+}
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+fn encoding_to_u8(encoding: Encoding) -> u8 {
+    match encoding {
+        Encoding::Compressed => 0,
+        Encoding::Uncompressed => 1,
+    }
+}
+
+fn encoding_from_u8(encoding: u8) -> Result<Encoding, SetupFileError> {
+    match encoding {
+        0 => Ok(Encoding::Compressed),
+        1 => Ok(Encoding::Uncompressed),
+        _ => Err(SetupFileError::UnknownEncoding(encoding)),
+    }
+}
+
+#[derive(Debug)]
+pub enum SetupFileError {
+    Io(std::io::Error),
+    Serialization(SerializationError),
+    /// the file does not start with `SETUP_MAGIC`
+    NotASetupFile,
+    UnsupportedVersion(u8),
+    UnknownSection(u8),
+    UnknownEncoding(u8),
+    /// only uncompressed files can be memory-mapped
+    NotMappable,
+    CurveMismatch {
+        expected: String,
+        found: String,
+    },
+    SectionMismatch {
+        expected: Section,
+        found: Section,
+    },
+    LengthMismatch {
+        len: u64,
+        found: usize,
+    },
+    HashMismatch,
+}
+
+impl fmt::Display for SetupFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "could not read setup file: {}", err),
+            Self::Serialization(err) => write!(f, "malformed setup file: {}", err),
+            Self::NotASetupFile => write!(f, "not a setup file"),
+            Self::UnsupportedVersion(v) => write!(f, "unsupported setup file version {}", v),
+            Self::UnknownSection(section) => write!(f, "unknown setup section {}", section),
+            Self::UnknownEncoding(encoding) => write!(f, "unknown point encoding {}", encoding),
+            Self::NotMappable => write!(f, "only uncompressed setup files can be memory-mapped"),
+            Self::CurveMismatch { expected, found } => {
+                write!(f, "setup is for {} but {} was expected", found, expected)
+            }
+            Self::SectionMismatch { expected, found } => {
+                write!(f, "file holds {} but {} was expected", found, expected)
+            }
+            Self::LengthMismatch { len, found } => {
+                write!(
+                    f,
+                    "header declares {} elements but the file holds {}",
+                    len, found
+                )
+            }
+            Self::HashMismatch => write!(f, "setup file content does not match its hash"),
+        }
+    }
+}
+
+impl std::error::Error for SetupFileError {}
+
+impl From<std::io::Error> for SetupFileError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<SerializationError> for SetupFileError {
+    fn from(err: SerializationError) -> Self {
+        Self::Serialization(err)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SetupHeader {
+    pub curve: String,
+    pub section: Section,
+    pub encoding: Encoding,
+    pub table: Option<TableFingerprint>,
+    pub len: u64,
+    pub hash: [u8; 32],
+}
+
+impl SetupHeader {
+    /// Parses the header, returns it together with the serialized data that follows it
+    pub fn parse(bytes: &[u8]) -> Result<(Self, &[u8]), SetupFileError> {
+        if !bytes.starts_with(&SETUP_MAGIC) {
+            return Err(SetupFileError::NotASetupFile);
+        }
+
+        let mut reader = &bytes[SETUP_MAGIC.len()..];
+        let version = u8::deserialize_compressed(&mut reader)?;
+        if version != SETUP_VERSION {
+            return Err(SetupFileError::UnsupportedVersion(version));
+        }
+
+        let curve = String::deserialize_compressed(&mut reader)?;
+        let section = Section::from_u8(u8::deserialize_compressed(&mut reader)?)?;
+        let header = Self {
+            curve,
+            section,
+            encoding: encoding_from_u8(u8::deserialize_compressed(&mut reader)?)?,
+            table: Option::<TableFingerprint>::deserialize_compressed(&mut reader)?,
+            len: u64::deserialize_compressed(&mut reader)?,
+            hash: <[u8; 32]>::deserialize_compressed(&mut reader)?,
+        };
+        Ok((header, reader))
+    }
+
+    /// Hash an intact file records for `data_bytes` under this header, so that neither the
+    /// data nor the table, curve, section or encoding can be changed without it
+    pub(crate) fn content_hash(&self, data_bytes: &[u8]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update([SETUP_VERSION]);
+        hasher.update(self.field_bytes());
+        hasher.update(data_bytes);
+        hasher.finalize().into()
+    }
+
+    /// Serialized fields between the version and the hash
+    fn field_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        self.curve.serialize_compressed(&mut bytes).unwrap();
+        self.section
+            .to_u8()
+            .serialize_compressed(&mut bytes)
+            .unwrap();
+        encoding_to_u8(self.encoding)
+            .serialize_compressed(&mut bytes)
+            .unwrap();
+        self.table.serialize_compressed(&mut bytes).unwrap();
+        self.len.serialize_compressed(&mut bytes).unwrap();
+        bytes
+    }
+
+    /// Checks that the file holds `section` of a setup on `curve`
+    pub fn expect(&self, curve: &str, section: Section) -> Result<(), SetupFileError> {
+        if self.curve != curve {
+            return Err(SetupFileError::CurveMismatch {
+                expected: curve.to_string(),
+                found: self.curve.clone(),
+            });
+        }
+        if self.section != section {
+            return Err(SetupFileError::SectionMismatch {
+                expected: section,
+                found: self.section,
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks that the file was computed for the table with `fingerprint`
+    pub fn expect_table(&self, fingerprint: &TableFingerprint) -> Result<(), SetupError> {
+        if self.table != Some(*fingerprint) {
+            return Err(SetupError::TableMismatch {
+                expected: *fingerprint,
+                found: self.table,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// A single section of the setup together with the header describing it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SetupFile<T> {
+    pub header: SetupHeader,
+    pub data: Vec<T>,
+}
+
+impl<T: CanonicalSerialize + CanonicalDeserialize + Send> SetupFile<T> {
+    pub fn new(curve: &str, section: Section, data: Vec<T>) -> Self {
+        Self::with_encoding(curve, section, Encoding::Compressed, data)
+    }
+
+    pub fn with_encoding(curve: &str, section: Section, encoding: Encoding, data: Vec<T>) -> Self {
+        let mut file = Self {
+            header: SetupHeader {
+                curve: curve.to_string(),
+                section,
+                encoding,
+                table: None,
+                len: data.len() as u64,
+                hash: [0; 32],
+            },
+            data,
+        };
+        file.rehash();
+        file
+    }
+
+    /// Records the table the section was computed for
+    pub fn for_table(mut self, fingerprint: TableFingerprint) -> Self {
+        self.header.table = Some(fingerprint);
+        self.rehash();
+        self
+    }
+
+    /// The same section in `encoding`, still recording its table
+    pub fn into_encoding(mut self, encoding: Encoding) -> Self {
+        self.header.encoding = encoding;
+        self.rehash();
+        self
+    }
+
+    fn data_bytes(&self) -> Vec<u8> {
+        let mut data_bytes = vec![];
+        self.data
+            .serialize_with_mode(&mut data_bytes, self.header.encoding.compress())
+            .unwrap();
+        data_bytes
+    }
+
+    /// Recomputes the hash after a header field changed
+    fn rehash(&mut self) {
+        self.header.hash = self.header.content_hash(&self.data_bytes());
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        let header = &self.header;
+        SETUP_MAGIC.serialize_compressed(&mut bytes).unwrap();
+        SETUP_VERSION.serialize_compressed(&mut bytes).unwrap();
+        bytes.extend(header.field_bytes());
+        header.hash.serialize_compressed(&mut bytes).unwrap();
+        self.data
+            .serialize_with_mode(&mut bytes, header.encoding.compress())
+            .unwrap();
+        bytes
+    }
+
+    /// Parses and validates a setup file of the given curve and section
+    pub fn from_bytes(bytes: &[u8], curve: &str, section: Section) -> Result<Self, SetupFileError> {
+        Self::from_bytes_with_validation(bytes, curve, section, Validation::Validated)
+    }
+
+    /// Like `from_bytes`, trusted files skip the point checks but not the hash
+    pub fn from_bytes_with_validation(
+        bytes: &[u8],
+        curve: &str,
+        section: Section,
+        validation: Validation,
+    ) -> Result<Self, SetupFileError> {
+        let (header, data_bytes) = SetupHeader::parse(bytes)?;
+        header.expect(curve, section)?;
+        if header.content_hash(data_bytes) != header.hash {
+            return Err(SetupFileError::HashMismatch);
+        }
+
+        let data: Vec<T> = deserialize_vec_from_slice(data_bytes, header.encoding, validation)?;
+        if data.len() as u64 != header.len {
+            return Err(SetupFileError::LengthMismatch {
+                len: header.len,
+                found: data.len(),
+            });
+        }
+        Ok(Self { header, data })
+    }
+
+    /// Like `from_bytes`, and rejects the file unless it was computed for the table with
+    /// `fingerprint`
+    pub fn from_bytes_for_table(
+        bytes: &[u8],
+        curve: &str,
+        section: Section,
+        fingerprint: &TableFingerprint,
+    ) -> Result<Self, SetupError> {
+        let file = Self::from_bytes(bytes, curve, section)?;
+        file.header.expect_table(fingerprint)?;
+        Ok(file)
+    }
+
+    pub fn from_reader<R: Read>(
+        mut reader: R,
+        curve: &str,
+        section: Section,
+        validation: Validation,
+    ) -> Result<Self, SetupFileError> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+        Self::from_bytes_with_validation(&bytes, curve, section, validation)
+    }
+
+    pub fn read(path: &str, curve: &str, section: Section) -> Result<Self, SetupFileError> {
+        Self::read_with_validation(path, curve, section, Validation::Validated)
+    }
+
+    /// Like `read`, and rejects the file unless it was computed for the table with `fingerprint`
+    pub fn read_for_table(
+        path: &str,
+        curve: &str,
+        section: Section,
+        fingerprint: &TableFingerprint,
+    ) -> Result<Self, SetupError> {
+        let file = Self::read(path, curve, section)?;
+        file.header.expect_table(fingerprint)?;
+        Ok(file)
+    }
+
+    pub fn read_with_validation(
+        path: &str,
+        curve: &str,
+        section: Section,
+        validation: Validation,
+    ) -> Result<Self, SetupFileError> {
+        Self::from_reader(std::fs::File::open(path)?, curve, section, validation)
+    }
+
+    pub fn write(&self, path: &str) -> Result<(), SetupError> {
+        crate::write_bytes(path, &self.to_bytes())
+    }
+}
+
+#[cfg(test)]
+mod setup_file_test {
+    use super::{
+        Encoding, Section, SetupFile, SetupFileError, SetupHeader, SETUP_MAGIC, SETUP_VERSION,
+    };
+    use crate::{compute_g_powers, gen_table, SetupError, TableFingerprint, Validation};
+    use ark_bn254::{Fr, G1Affine, G1Projective};
+
+    #[test]
+    fn test_setup_file() {
+        let powers = compute_g_powers::<G1Projective>(Fr::from(100u64), 8);
+        let file = SetupFile::new("bn254", Section::G1Powers, powers);
+        let bytes = file.to_bytes();
+
+        let (header, _) = SetupHeader::parse(&bytes).unwrap();
+        assert_eq!(header, file.header);
+        assert_eq!(header.len, 8);
+        let read = SetupFile::<G1Affine>::from_bytes(&bytes, "bn254", Section::G1Powers).unwrap();
+        assert_eq!(read, file);
+
+        assert!(matches!(
+            SetupFile::<G1Affine>::from_bytes(&bytes, "bls12-381", Section::G1Powers),
+            Err(SetupFileError::CurveMismatch { .. })
+        ));
+        assert!(matches!(
+            SetupFile::<G1Affine>::from_bytes(&bytes, "bn254", Section::Qs),
+            Err(SetupFileError::SectionMismatch {
+                expected: Section::Qs,
+                found: Section::G1Powers
+            })
+        ));
+
+        let mut corrupted = bytes.clone();
+        *corrupted.last_mut().unwrap() ^= 1;
+        assert!(matches!(
+            SetupFile::<G1Affine>::from_bytes(&corrupted, "bn254", Section::G1Powers),
+            Err(SetupFileError::HashMismatch)
+        ));
+        assert!(matches!(
+            SetupHeader::parse(&bytes[1..]),
+            Err(SetupFileError::NotASetupFile)
+        ));
+
+        // only the current version is read, earlier ones did not hash their header
+        let mut old = bytes.clone();
+        old[SETUP_MAGIC.len()] = SETUP_VERSION - 1;
+        assert!(matches!(
+            SetupHeader::parse(&old),
+            Err(SetupFileError::UnsupportedVersion(v)) if v == SETUP_VERSION - 1
+        ));
+    }
+
+    #[test]
+    fn test_encodings() {
+        let powers = compute_g_powers::<G1Projective>(Fr::from(100u64), 8);
+        let compressed = SetupFile::new("bn254", Section::G1Powers, powers.clone());
+        let uncompressed = SetupFile::with_encoding(
+            "bn254",
+            Section::G1Powers,
+            Encoding::Uncompressed,
+            powers.clone(),
+        );
+        assert_ne!(compressed.header.hash, uncompressed.header.hash);
+        let bytes = uncompressed.to_bytes();
+        assert!(bytes.len() > compressed.to_bytes().len());
+        let read = SetupFile::<G1Affine>::from_bytes(&bytes, "bn254", Section::G1Powers).unwrap();
+        assert_eq!(read.header.encoding, Encoding::Uncompressed);
+        assert_eq!(read.data, powers);
+        let trusted = SetupFile::<G1Affine>::from_reader(
+            bytes.as_slice(),
+            "bn254",
+            Section::G1Powers,
+            Validation::Trusted,
+        )
+        .unwrap();
+        assert_eq!(trusted, read);
+    }
+
+    #[test]
+    fn test_table_binding() {
+        let t = gen_table::<Fr>(3, Some("binding")).unwrap();
+        let other = gen_table::<Fr>(3, Some("other")).unwrap();
+        let fingerprint = TableFingerprint::from_poly("bn254", &t, 8).unwrap();
+        let other_fingerprint = TableFingerprint::from_poly("bn254", &other, 8).unwrap();
+
+        let powers = compute_g_powers::<G1Projective>(Fr::from(100u64), 8);
+        let unbound = SetupFile::new("bn254", Section::Qs, powers);
+        let file = unbound.clone().for_table(fingerprint);
+        let read =
+            SetupFile::<G1Affine>::from_bytes(&file.to_bytes(), "bn254", Section::Qs).unwrap();
+        assert_eq!(read.header.table, Some(fingerprint));
+        read.header.expect_table(&fingerprint).unwrap();
+
+        assert!(matches!(
+            read.header.expect_table(&other_fingerprint),
+            Err(SetupError::TableMismatch { found: Some(found), .. }) if found == fingerprint
+        ));
+        assert!(matches!(
+            unbound.header.expect_table(&fingerprint),
+            Err(SetupError::TableMismatch { found: None, .. })
+        ));
+
+        let bytes = file.to_bytes();
+        SetupFile::<G1Affine>::from_bytes_for_table(&bytes, "bn254", Section::Qs, &fingerprint)
+            .unwrap();
+        let uncompressed = file
+            .clone()
+            .into_encoding(Encoding::Uncompressed)
+            .to_bytes();
+        let read = SetupFile::<G1Affine>::from_bytes_for_table(
+            &uncompressed,
+            "bn254",
+            Section::Qs,
+            &fingerprint,
+        )
+        .unwrap();
+        assert_eq!(read.header.encoding, Encoding::Uncompressed);
+        assert_eq!(read.data, file.data);
+        assert!(matches!(
+            SetupFile::<G1Affine>::from_bytes_for_table(
+                &bytes,
+                "bn254",
+                Section::Qs,
+                &other_fingerprint
+            ),
+            Err(SetupError::TableMismatch { .. })
+        ));
+
+        // the hash covers the header, relabeling the table breaks it
+        let mut relabeled = file.clone();
+        relabeled.header.table = Some(other_fingerprint);
+        assert!(matches!(
+            SetupFile::<G1Affine>::from_bytes(&relabeled.to_bytes(), "bn254", Section::Qs),
+            Err(SetupFileError::HashMismatch)
+        ));
+    }
+}
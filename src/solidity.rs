@@ -0,0 +1,177 @@
+use crate::error::SetupError;
+use crate::evm::{g1_to_evm, g2_to_evm};
+use crate::utils::is_pow_2;
+use crate::vk::VerifierKey;
+use ark_bn254::{Bn254, Fq, Fr, G1Affine};
+use ark_ff::{BigInteger, Field, PrimeField};
+use num_bigint::BigUint;
+use tiny_keccak::{Hasher, Keccak};
+
+const VERIFIER_TEMPLATE: &str = include_str!("cq_verifier.sol");
+const TRANSCRIPT_DOMAIN: &[u8] = b"mock-cq-setup/cq_transcript/v1";
+
+pub fn keccak256(bytes: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    hasher.update(bytes);
+    let mut hash = [0u8; 32];
+    hasher.finalize(&mut hash);
+    hash
+}
+
+/// Fiat-Shamir transcript of the generated verifier, so a prover derives the same challenges.
+/// Every challenge hashes the previous challenge, or the domain, table and sizes for the
+/// first one, followed by the values appended since.
+pub struct EvmTranscript {
+    buffer: Vec<u8>,
+}
+
+impl EvmTranscript {
+    pub fn new(vk: &VerifierKey<Bn254>) -> Self {
+        let mut buffer = keccak256(TRANSCRIPT_DOMAIN).to_vec();
+        buffer.extend_from_slice(&vk.table.0);
+        for size in [vk.table_n, vk.circuit_n] {
+            buffer.extend_from_slice(&[0u8; 24]);
+            buffer.extend_from_slice(&size.to_be_bytes());
+        }
+        Self { buffer }
+    }
+
+    pub fn append_g1(&mut self, p: &G1Affine) {
+        for coordinate in g1_to_evm(p) {
+            self.append_word(&coordinate);
+        }
+    }
+
+    pub fn append_scalar(&mut self, s: &Fr) {
+        self.buffer
+            .extend_from_slice(&s.into_bigint().to_bytes_be());
+    }
+
+    fn append_word(&mut self, x: &BigUint) {
+        let bytes = x.to_bytes_be();
+        self.buffer.resize(self.buffer.len() + 32 - bytes.len(), 0);
+        self.buffer.extend_from_slice(&bytes);
+    }
+
+    pub fn challenge(&mut self) -> Fr {
+        let challenge = Fr::from_be_bytes_mod_order(&keccak256(&self.buffer));
+        self.buffer = challenge.into_bigint().to_bytes_be();
+        challenge
+    }
+}
+
+/// Solidity contract that verifies cq proofs for the table and circuit size of `vk`. The key
+/// holds the points of `CommonPreprocessedInput` together with [1]_2 and [tau]_2.
+pub fn solidity_verifier(vk: &VerifierKey<Bn254>) -> Result<String, SetupError> {
+    for size in [vk.table_n as usize, vk.circuit_n as usize] {
+        if !is_pow_2(size) {
+            return Err(SetupError::NotPow2 { size });
+        }
+    }
+    let b0_scale = Fr::from(vk.table_n) * Fr::from(vk.circuit_n).inverse().unwrap();
+
+    let mut values = vec![
+        ("q".to_string(), BigUint::from(Fq::MODULUS)),
+        ("r".to_string(), BigUint::from(Fr::MODULUS)),
+        ("table_n".to_string(), BigUint::from(vk.table_n)),
+        ("circuit_n".to_string(), BigUint::from(vk.circuit_n)),
+        (
+            "circuit_k".to_string(),
+            BigUint::from(vk.circuit_n.trailing_zeros()),
+        ),
+        ("b0_scale".to_string(), b0_scale.into_bigint().into()),
+    ];
+    for (name, point) in [
+        ("g2", &vk.g2),
+        ("tau_2", &vk.tau_2),
+        ("zv_2", &vk.zv_2),
+        ("t_2", &vk.t_2),
+        ("x_b0_bound", &vk.x_b0_bound),
+    ] {
+        let coordinates = ["x1", "x0", "y1", "y0"].iter().zip(g2_to_evm(point));
+        values.extend(coordinates.map(|(c, value)| (format!("{}_{}", name, c), value)));
+    }
+
+    let contract = VERIFIER_TEMPLATE.replace("{table}", &format!("0x{}", vk.table));
+    Ok(values.iter().fold(contract, |contract, (name, value)| {
+        contract.replace(&format!("{{{}}}", name), &value.to_string())
+    }))
+}
+
+#[cfg(test)]
+mod solidity_test {
+    use super::{keccak256, solidity_verifier, EvmTranscript};
+    use crate::{g2_to_evm, gen_table, setup, TableFingerprint, VerifierKey};
+    use ark_bn254::{Bn254, Fr};
+    use ark_ec::AffineRepr;
+    use ark_ff::{BigInteger, PrimeField};
+    use num_bigint::BigUint;
+
+    #[test]
+    fn test_solidity_verifier() {
+        let tau = Fr::from(100u64);
+        let (table_n, circuit_n) = (1 << 3, 1 << 4);
+        let t = gen_table::<Fr>(3, Some("solidity")).unwrap();
+        let output = setup::<Bn254>(tau, table_n, circuit_n, &t).unwrap();
+        let fingerprint = TableFingerprint::from_poly("bn254", &t, table_n).unwrap();
+        let vk =
+            VerifierKey::<Bn254>::from_srs(&output.g2_powers, &t, table_n, circuit_n, fingerprint)
+                .unwrap();
+
+        assert_eq!(
+            hex(&keccak256(b"")),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        let contract = solidity_verifier(&vk).unwrap();
+        for (name, point) in [
+            ("G2", vk.g2),
+            ("TAU_2", vk.tau_2),
+            ("ZV_2", vk.zv_2),
+            ("T_2", vk.t_2),
+            ("X_B0_BOUND", vk.x_b0_bound),
+        ] {
+            let (x, y) = point.xy().unwrap();
+            let coordinates = [x.c1, x.c0, y.c1, y.c0].map(|c| BigUint::from(c.into_bigint()));
+            assert_eq!(g2_to_evm(&point), coordinates);
+            for (suffix, value) in ["X1", "X0", "Y1", "Y0"].iter().zip(&coordinates) {
+                let constant = format!("uint256 constant {}_{} = {};", name, suffix, value);
+                assert!(contract.contains(&constant), "{}", constant);
+            }
+        }
+        assert!(contract.contains(&format!("bytes32 constant TABLE = 0x{};", fingerprint)));
+        assert!(contract.contains("uint256 constant CIRCUIT_K = 4;"));
+        assert!(!contract.contains("= {"));
+
+        // beta = keccak256(keccak256(domain) || table || N || n || f || m) mod r
+        let (f, m) = (
+            output.lagrange_commitments[1],
+            output.lagrange_commitments[2],
+        );
+        let mut transcript = EvmTranscript::new(&vk);
+        transcript.append_g1(&f);
+        transcript.append_g1(&m);
+        let beta = transcript.challenge();
+        let mut bytes = keccak256(b"mock-cq-setup/cq_transcript/v1").to_vec();
+        bytes.extend_from_slice(&fingerprint.0);
+        bytes.extend_from_slice(&Fr::from(8u64).into_bigint().to_bytes_be());
+        bytes.extend_from_slice(&Fr::from(16u64).into_bigint().to_bytes_be());
+        for point in [f, m] {
+            let (x, y) = point.xy().unwrap();
+            bytes.extend_from_slice(&x.into_bigint().to_bytes_be());
+            bytes.extend_from_slice(&y.into_bigint().to_bytes_be());
+        }
+        assert_eq!(beta, Fr::from_be_bytes_mod_order(&keccak256(&bytes)));
+
+        transcript.append_scalar(&Fr::from(5u64));
+        let mut bytes = beta.into_bigint().to_bytes_be();
+        bytes.extend_from_slice(&Fr::from(5u64).into_bigint().to_bytes_be());
+        assert_eq!(
+            transcript.challenge(),
+            Fr::from_be_bytes_mod_order(&keccak256(&bytes))
+        );
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
@@ -1,35 +1,28 @@
 #[cfg(feature = "parallel")]
 use crate::utils::parallelize;
 use ark_ec::CurveGroup;
-#[cfg(feature = "parallel")]
-use rayon::{self, prelude::*};
 
 /// [tau^0]G, ..., [tau^{n-1}]G
 pub fn compute_g_powers<G: CurveGroup>(tau: G::ScalarField, n: usize) -> Vec<G::Affine> {
-    let mut g_srs = vec![G::zero(); n - 1];
-
     #[cfg(not(feature = "parallel"))]
-    let g_srs: Vec<G> = std::iter::once(G::generator())
-        .chain(g_srs.iter().scan(G::generator(), |state, _| {
-            *state *= &tau;
-            Some(*state)
-        }))
+    let g_srs: Vec<G> = std::iter::successors(Some(G::generator()), |state| Some(*state * tau))
+        .take(n)
         .collect();
 
     #[cfg(feature = "parallel")]
-    {
+    let g_srs: Vec<G> = {
         use ark_ff::Field;
-        use ark_ff::Zero;
-        g_srs.push(G::zero());
+        let mut g_srs = vec![G::zero(); n];
         parallelize(&mut g_srs, |g, start| {
             let mut current_g: G = G::generator();
-            current_g = current_g.mul(tau.pow(&[start as u64]));
+            current_g = current_g.mul(tau.pow([start as u64]));
             for g in g.iter_mut() {
                 *g = current_g;
                 current_g *= tau;
             }
         });
-    }
+        g_srs
+    };
 
     G::normalize_batch(&g_srs)
 }
@@ -62,11 +55,42 @@ mod powers_test {
         use ark_bn254::{Bn254, Fr, G1Projective};
         let k = 1;
         let n = 1 << k;
-        let tau = Fr::from(100 as u64);
+        let tau = Fr::from(100u64);
 
         let srs_sanity = sanity_srs::<Bn254>(n, tau);
         let g1_srs = super::compute_g_powers::<G1Projective>(tau, n);
 
         assert_eq!(srs_sanity, g1_srs);
     }
+
+    // cargo test --features=parallel consistency_g_powers
+    #[test]
+    fn consistency_g_powers() {
+        use crate::utils::run_with_threads;
+        use ark_bn254::{Bn254, Fr, G1Projective, G2Projective};
+        use ark_ec::{CurveGroup, Group};
+        use ark_ff::Field;
+        use ark_std::UniformRand;
+
+        let mut rng = ark_std::test_rng();
+        for k in 0..=6 {
+            let n = 1 << k;
+            let tau = Fr::rand(&mut rng);
+
+            let g1_powers = run_with_threads(|| super::compute_g_powers::<G1Projective>(tau, n));
+            assert_eq!(g1_powers, sanity_srs::<Bn254>(n, tau));
+
+            // the G2 SRS is one longer than the G1 SRS
+            let g2_powers =
+                run_with_threads(|| super::compute_g_powers::<G2Projective>(tau, n + 1));
+            let naive_g2: Vec<_> = (0..=n)
+                .map(|i| {
+                    G2Projective::generator()
+                        .mul(tau.pow([i as u64]))
+                        .into_affine()
+                })
+                .collect();
+            assert_eq!(g2_powers, naive_g2);
+        }
+    }
 }
@@ -1,29 +1,142 @@
-use ark_ff::FftField;
+use crate::error::{domain_of_log_size, SetupError};
+use ark_ff::{FftField, PrimeField};
 use ark_poly::DenseUVPolynomial;
 use ark_poly::{univariate::DensePolynomial, EvaluationDomain, GeneralEvaluationDomain};
-use rand::rngs::StdRng;
-use rand::SeedableRng;
+use ark_std::cfg_chunks_mut;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use sha2::{Digest, Sha256};
 
-/// Generates random table from seed
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Domain separation tag hashed together with the seed
+pub const TABLE_SEED_DST: &[u8] = b"mock-cq-setup/gen_table/v1";
+/// Number of entries drawn from a single ChaCha20 stream
+pub const TABLE_CHUNK_SIZE: usize = 1 << 12;
+
+/*
+    Seeded tables only depend on the seed, never on the platform, the thread count or the
+    version of `rand`:
+
+    key      = sha256(TABLE_SEED_DST || seed)
+    chunk c  = entries [c * TABLE_CHUNK_SIZE, (c + 1) * TABLE_CHUNK_SIZE)
+    stream   = ChaCha20 (20 rounds) keyed with `key`, nonce = 64-bit block counter starting at 0
+               followed by the 64-bit stream id c, both little endian
+    entry    = next 64 bytes of the stream read as a little endian integer, reduced mod p
+*/
+fn seeded_evals<F: PrimeField>(n: usize, seed_string: &str) -> Vec<F> {
+    let mut hasher = Sha256::new();
+    hasher.update(TABLE_SEED_DST);
+    hasher.update(seed_string.as_bytes());
+    let key: [u8; 32] = hasher.finalize().into();
+
+    let mut t_evals = vec![F::zero(); n];
+    cfg_chunks_mut!(t_evals, TABLE_CHUNK_SIZE)
+        .enumerate()
+        .for_each(|(chunk_num, chunk)| {
+            let mut rng = ChaCha20Rng::from_seed(key);
+            rng.set_stream(chunk_num as u64);
+            let mut bytes = [0u8; 64];
+            for t_i in chunk.iter_mut() {
+                rng.fill_bytes(&mut bytes);
+                *t_i = F::from_le_bytes_mod_order(&bytes);
+            }
+        });
+    t_evals
+}
+
+/// Generates random table from seed, see `seeded_evals` for how entries are derived
 /// If seed is not provided, table is just simple sequence: [1..n]
-pub fn gen_table<F: FftField>(k: usize, seed_string: Option<&str>) -> DensePolynomial<F> {
-    let n = 1 << k;
-    let domain = GeneralEvaluationDomain::<F>::new(n).unwrap();
+pub fn gen_table<F: PrimeField>(
+    k: usize,
+    seed_string: Option<&str>,
+) -> Result<DensePolynomial<F>, SetupError> {
+    let domain = domain_of_log_size::<F>(k)?;
+    let n = domain.size();
 
     let t_evals: Vec<F> = match seed_string {
-        Some(seed_string) => {
-            let seed: [u8; 32] = {
-                let mut seed_bytes = [0; 32];
-                let string_bytes: &[u8] = seed_string.as_bytes();
-                let take = std::cmp::min(32, string_bytes.len());
-                seed_bytes[0..take].copy_from_slice(&string_bytes[..take]);
-                seed_bytes
-            };
-            let mut rng = StdRng::from_seed(seed);
-            (0..n).map(|_| F::rand(&mut rng)).collect()
-        }
+        Some(seed_string) => seeded_evals(n, seed_string),
         None => (0..n).map(|i| F::from((i + 1) as u64)).collect(),
     };
 
-    DensePolynomial::<F>::from_coefficients_slice(&domain.ifft(&t_evals))
+    interpolate_table(&domain, &t_evals)
+}
+
+/// Table polynomial from its evaluations over `domain`
+pub fn interpolate_table<F: FftField>(
+    domain: &GeneralEvaluationDomain<F>,
+    t_evals: &[F],
+) -> Result<DensePolynomial<F>, SetupError> {
+    if t_evals.len() != domain.size() {
+        return Err(SetupError::SizeMismatch {
+            expected: domain.size(),
+            found: t_evals.len(),
+        });
+    }
+    Ok(DensePolynomial::<F>::from_coefficients_slice(
+        &domain.ifft(t_evals),
+    ))
+}
+
+#[cfg(test)]
+mod table_test {
+    use super::{seeded_evals, TABLE_CHUNK_SIZE};
+    use ark_bn254::Fr;
+    use ark_ff::{BigInteger, PrimeField};
+
+    fn hex(x: &Fr) -> String {
+        x.into_bigint()
+            .to_bytes_be()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    // regression fixture, changing it breaks every seeded table ever generated
+    #[test]
+    fn test_seeded_evals_are_stable() {
+        let evals = seeded_evals::<Fr>(2 * TABLE_CHUNK_SIZE, "LAI");
+
+        assert_eq!(hex(&evals[0]), FIRST);
+        assert_eq!(hex(&evals[TABLE_CHUNK_SIZE]), SECOND_CHUNK_FIRST);
+        assert_eq!(
+            seeded_evals::<Fr>(TABLE_CHUNK_SIZE + 1, "LAI")[..],
+            evals[..TABLE_CHUNK_SIZE + 1]
+        );
+    }
+
+    // cargo test --features=parallel consistency_gen_table
+    #[test]
+    fn consistency_gen_table() {
+        use crate::{gen_table, utils::run_with_threads};
+        use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+
+        for k in 0..=6 {
+            let n = 1 << k;
+            let domain = GeneralEvaluationDomain::<Fr>::new(n).unwrap();
+
+            let range = run_with_threads(|| gen_table::<Fr>(k, None).unwrap());
+            let expected: Vec<Fr> = (1..=n as u64).map(Fr::from).collect();
+            assert_eq!(domain.fft(&range.coeffs), expected);
+
+            let seeded = run_with_threads(|| gen_table::<Fr>(k, Some("consistency")).unwrap());
+            assert_eq!(seeded, gen_table::<Fr>(k, Some("consistency")).unwrap());
+            assert!(seeded.coeffs.len() <= n);
+        }
+
+        // k = 13 spans two chunks, so threads really generate different chunks
+        let n = 1 << 13;
+        assert!(n > TABLE_CHUNK_SIZE);
+        let evals = run_with_threads(|| seeded_evals::<Fr>(n, "LAI"));
+        assert_eq!(hex(&evals[0]), FIRST);
+        assert_eq!(hex(&evals[TABLE_CHUNK_SIZE]), SECOND_CHUNK_FIRST);
+        let table = run_with_threads(|| gen_table::<Fr>(13, Some("LAI")).unwrap());
+        let domain = GeneralEvaluationDomain::<Fr>::new(n).unwrap();
+        assert_eq!(domain.fft(&table.coeffs), evals);
+    }
+
+    const FIRST: &str = "0971039df4a07fd91147d929758fa13ce75d172286d6251bd0968ee6002a38ef";
+    const SECOND_CHUNK_FIRST: &str =
+        "25b099bc86ed8640842adaa335dc7ac7368c3397b154736525c7b2bb467242e2";
 }
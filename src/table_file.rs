@@ -0,0 +1,409 @@
+use crate::error::{domain, SetupError};
+use crate::fingerprint::TableFingerprint;
+use crate::import::Padding;
+use crate::table::interpolate_table;
+use ark_ff::FftField;
+use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial, EvaluationDomain};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+/*
+    Table file layout, every field is written with `serialize_compressed`:
+
+    magic       [u8; 8]   b"CQTABLE\0"
+    version     u8
+    curve       String    e.g. "bn254"
+    k           u32       the file holds exactly 2^k field elements
+    form        u8        0 = evaluations over the 2^k subgroup, 1 = coefficients
+    padding     u8        0 = none, 1 = zero, 2 = repeat-last, 3 = exact
+    spec        String    how the table was generated, e.g. "xor" or "import:opcodes.csv"
+    hash        [u8; 32]  sha256 of the version, the fields above and the serialized data
+    data        Vec<F>
+
+    Files without the magic are legacy bare `Vec<F>` of coefficients.
+*/
+
+pub const TABLE_MAGIC: [u8; 8] = *b"CQTABLE\0";
+pub const TABLE_VERSION: u8 = 2;
+/// Spec given to tables read from files without a header
+pub const LEGACY_SPEC: &str = "legacy";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TableForm {
+    Evaluations,
+    Coefficients,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TableHeader {
+    pub curve: String,
+    pub k: u32,
+    pub form: TableForm,
+    /// padding that was applied to imported tables
+    pub padding: Option<Padding>,
+    pub spec: String,
+    pub hash: [u8; 32],
+}
+
+#[derive(Debug)]
+pub enum TableFileError {
+    Io(std::io::Error),
+    Serialization(SerializationError),
+    UnsupportedVersion(u8),
+    UnknownForm(u8),
+    UnknownPadding(u8),
+    CurveMismatch {
+        expected: String,
+        found: String,
+    },
+    SizeMismatch {
+        k: u32,
+        len: usize,
+    },
+    /// tables hold 2^k entries
+    NotPow2 {
+        len: usize,
+    },
+    HashMismatch,
+}
+
+impl fmt::Display for TableFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "could not read table file: {}", err),
+            Self::Serialization(err) => write!(f, "malformed table file: {}", err),
+            Self::UnsupportedVersion(v) => write!(f, "unsupported table file version {}", v),
+            Self::UnknownForm(form) => write!(f, "unknown table form {}", form),
+            Self::UnknownPadding(padding) => write!(f, "unknown padding policy {}", padding),
+            Self::CurveMismatch { expected, found } => {
+                write!(f, "table is for {} but {} was expected", found, expected)
+            }
+            Self::SizeMismatch { k, len } => {
+                write!(
+                    f,
+                    "header declares 2^{} entries but the file holds {}",
+                    k, len
+                )
+            }
+            Self::NotPow2 { len } => write!(f, "table has {} entries, not a power of two", len),
+            Self::HashMismatch => write!(f, "table content does not match its hash"),
+        }
+    }
+}
+
+impl std::error::Error for TableFileError {}
+
+impl From<std::io::Error> for TableFileError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<SerializationError> for TableFileError {
+    fn from(err: SerializationError) -> Self {
+        Self::Serialization(err)
+    }
+}
+
+fn padding_to_u8(padding: Option<Padding>) -> u8 {
+    match padding {
+        None => 0,
+        Some(Padding::Zero) => 1,
+        Some(Padding::RepeatLast) => 2,
+        Some(Padding::Exact) => 3,
+    }
+}
+
+fn padding_from_u8(padding: u8) -> Result<Option<Padding>, TableFileError> {
+    match padding {
+        0 => Ok(None),
+        1 => Ok(Some(Padding::Zero)),
+        2 => Ok(Some(Padding::RepeatLast)),
+        3 => Ok(Some(Padding::Exact)),
+        _ => Err(TableFileError::UnknownPadding(padding)),
+    }
+}
+
+impl TableHeader {
+    /// Hash an intact file records for `data_bytes` under this header
+    pub(crate) fn content_hash(&self, data_bytes: &[u8]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update([TABLE_VERSION]);
+        hasher.update(self.field_bytes());
+        hasher.update(data_bytes);
+        hasher.finalize().into()
+    }
+
+    /// Serialized fields between the version and the hash
+    fn field_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        self.curve.serialize_compressed(&mut bytes).unwrap();
+        self.k.serialize_compressed(&mut bytes).unwrap();
+        let form = match self.form {
+            TableForm::Evaluations => 0u8,
+            TableForm::Coefficients => 1u8,
+        };
+        form.serialize_compressed(&mut bytes).unwrap();
+        padding_to_u8(self.padding)
+            .serialize_compressed(&mut bytes)
+            .unwrap();
+        self.spec.serialize_compressed(&mut bytes).unwrap();
+        bytes
+    }
+
+    /// Parses the header of a file that starts with `TABLE_MAGIC`, returns it together with
+    /// the serialized data that follows it
+    pub fn parse(bytes: &[u8]) -> Result<(Self, &[u8]), TableFileError> {
+        let mut reader = bytes.get(TABLE_MAGIC.len()..).unwrap_or_default();
+        let version = u8::deserialize_compressed(&mut reader)?;
+        if version != TABLE_VERSION {
+            return Err(TableFileError::UnsupportedVersion(version));
+        }
+
+        let curve = String::deserialize_compressed(&mut reader)?;
+        let k = u32::deserialize_compressed(&mut reader)?;
+        let form = match u8::deserialize_compressed(&mut reader)? {
+            0 => TableForm::Evaluations,
+            1 => TableForm::Coefficients,
+            form => return Err(TableFileError::UnknownForm(form)),
+        };
+        let header = Self {
+            curve,
+            k,
+            form,
+            padding: padding_from_u8(u8::deserialize_compressed(&mut reader)?)?,
+            spec: String::deserialize_compressed(&mut reader)?,
+            hash: <[u8; 32]>::deserialize_compressed(&mut reader)?,
+        };
+        Ok((header, reader))
+    }
+}
+
+/// Table entries or coefficients together with the header describing them
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TableFile<F: FftField> {
+    pub header: TableHeader,
+    pub data: Vec<F>,
+}
+
+impl<F: FftField> TableFile<F> {
+    /// `data` must hold exactly 2^k elements
+    pub fn new(
+        curve: &str,
+        form: TableForm,
+        padding: Option<Padding>,
+        spec: &str,
+        data: Vec<F>,
+    ) -> Result<Self, TableFileError> {
+        if !data.len().is_power_of_two() {
+            return Err(TableFileError::NotPow2 { len: data.len() });
+        }
+        let mut data_bytes = vec![];
+        data.serialize_compressed(&mut data_bytes)?;
+
+        let mut header = TableHeader {
+            curve: curve.to_string(),
+            k: data.len().trailing_zeros(),
+            form,
+            padding,
+            spec: spec.to_string(),
+            hash: [0; 32],
+        };
+        header.hash = header.content_hash(&data_bytes);
+        Ok(Self { header, data })
+    }
+
+    /// Stores the coefficients of `t`, zero extended to 2^k
+    pub fn from_poly(
+        curve: &str,
+        k: usize,
+        padding: Option<Padding>,
+        spec: &str,
+        t: &DensePolynomial<F>,
+    ) -> Result<Self, TableFileError> {
+        let mut coeffs = t.coeffs.clone();
+        if k >= usize::BITS as usize || coeffs.len() > 1 << k {
+            return Err(TableFileError::SizeMismatch {
+                k: k as u32,
+                len: coeffs.len(),
+            });
+        }
+        coeffs.resize(1 << k, F::zero());
+        Self::new(curve, TableForm::Coefficients, padding, spec, coeffs)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        let header = &self.header;
+        TABLE_MAGIC.serialize_compressed(&mut bytes).unwrap();
+        TABLE_VERSION.serialize_compressed(&mut bytes).unwrap();
+        bytes.extend_from_slice(&header.field_bytes());
+        header.hash.serialize_compressed(&mut bytes).unwrap();
+        self.data.serialize_compressed(&mut bytes).unwrap();
+        bytes
+    }
+
+    /// Parses and validates a table file. Legacy files are bare coefficients with trailing
+    /// zeroes trimmed, they are zero extended to the next power of two and get the spec
+    /// `LEGACY_SPEC`, so their declared size is only a lower bound.
+    pub fn from_bytes(bytes: &[u8], curve: &str) -> Result<Self, TableFileError> {
+        if !bytes.starts_with(&TABLE_MAGIC) {
+            let mut coeffs = Vec::<F>::deserialize_compressed(bytes)?;
+            let n = coeffs.len().next_power_of_two();
+            coeffs.resize(n, F::zero());
+            return Self::new(curve, TableForm::Coefficients, None, LEGACY_SPEC, coeffs);
+        }
+
+        let (header, data_bytes) = TableHeader::parse(bytes)?;
+        if header.curve != curve {
+            return Err(TableFileError::CurveMismatch {
+                expected: curve.to_string(),
+                found: header.curve,
+            });
+        }
+
+        if header.content_hash(data_bytes) != header.hash {
+            return Err(TableFileError::HashMismatch);
+        }
+        let data = Vec::<F>::deserialize_compressed(data_bytes)?;
+        let k = header.k;
+        if k >= usize::BITS || data.len() != 1 << k {
+            return Err(TableFileError::SizeMismatch { k, len: data.len() });
+        }
+
+        Ok(Self { header, data })
+    }
+
+    pub fn read(path: &str, curve: &str) -> Result<Self, TableFileError> {
+        let bytes = std::fs::read(path)?;
+        Self::from_bytes(&bytes, curve)
+    }
+
+    /// Same table in the requested form, the hash is recomputed for the new data
+    pub fn to_form(&self, form: TableForm) -> Result<Self, SetupError> {
+        if form == self.header.form {
+            return Ok(self.clone());
+        }
+
+        let domain = domain::<F>(self.data.len())?;
+        let data = match form {
+            TableForm::Evaluations => domain.fft(&self.data),
+            TableForm::Coefficients => domain.ifft(&self.data),
+        };
+        let header = &self.header;
+        Self::new(&header.curve, form, header.padding, &header.spec, data).map_err(Into::into)
+    }
+
+    /// Fingerprint of the table, the same in either form
+    pub fn fingerprint(&self) -> Result<TableFingerprint, SetupError> {
+        let evals = self.to_form(TableForm::Evaluations)?;
+        Ok(TableFingerprint::from_evaluations(
+            &self.header.curve,
+            &evals.data,
+        ))
+    }
+
+    /// Table polynomial, interpolated if the file holds evaluations
+    pub fn table(&self) -> Result<DensePolynomial<F>, SetupError> {
+        match self.header.form {
+            TableForm::Coefficients => Ok(DensePolynomial::from_coefficients_slice(&self.data)),
+            TableForm::Evaluations => interpolate_table(&domain(self.data.len())?, &self.data),
+        }
+    }
+}
+
+#[cfg(test)]
+mod table_file_test {
+    use super::{TableFile, TableFileError, TableForm, LEGACY_SPEC};
+    use crate::{gen_table, serialize_vec, Padding};
+    use ark_bn254::Fr;
+
+    #[test]
+    fn test_round_trip_and_forms() {
+        let t = gen_table::<Fr>(3, Some("table file")).unwrap();
+        let file = TableFile::from_poly("bn254", 3, None, "seeded:3:table file", &t).unwrap();
+
+        let read = TableFile::<Fr>::from_bytes(&file.to_bytes(), "bn254").unwrap();
+        assert_eq!(read, file);
+        assert_eq!(read.table().unwrap(), t);
+
+        let evals = file.to_form(TableForm::Evaluations).unwrap();
+        assert_eq!(evals.header.form, TableForm::Evaluations);
+        assert_ne!(evals.header.hash, file.header.hash);
+        assert_eq!(evals.table().unwrap(), t);
+        assert_eq!(evals.to_form(TableForm::Coefficients).unwrap(), file);
+        assert_eq!(evals.fingerprint().unwrap(), file.fingerprint().unwrap());
+
+        assert!(matches!(
+            TableFile::from_poly("bn254", 2, None, "", &t),
+            Err(TableFileError::SizeMismatch { k: 2, len: 8 })
+        ));
+    }
+
+    #[test]
+    fn test_validation() {
+        let evals: Vec<Fr> = (1..=4u64).map(Fr::from).collect();
+        let file = TableFile::new(
+            "bn254",
+            TableForm::Evaluations,
+            Some(Padding::Zero),
+            "",
+            evals,
+        )
+        .unwrap();
+        let bytes = file.to_bytes();
+
+        assert!(matches!(
+            TableFile::<Fr>::from_bytes(&bytes, "bls12-381"),
+            Err(TableFileError::CurveMismatch { .. })
+        ));
+
+        let mut corrupted = bytes.clone();
+        *corrupted.last_mut().unwrap() ^= 1;
+        assert!(matches!(
+            TableFile::<Fr>::from_bytes(&corrupted, "bn254"),
+            Err(TableFileError::HashMismatch)
+        ));
+
+        // the hash covers the header as well
+        let mut relabeled = file.clone();
+        relabeled.header.spec = "xor".to_string();
+        relabeled.header.padding = None;
+        assert!(matches!(
+            TableFile::<Fr>::from_bytes(&relabeled.to_bytes(), "bn254"),
+            Err(TableFileError::HashMismatch)
+        ));
+
+        // declare k = 3 for 4 entries
+        let mut wrong_size = file.clone();
+        wrong_size.header.k = 3;
+        wrong_size.header.hash = wrong_size.header.content_hash(&serialize_vec(&file.data));
+        assert!(matches!(
+            TableFile::<Fr>::from_bytes(&wrong_size.to_bytes(), "bn254"),
+            Err(TableFileError::SizeMismatch { k: 3, len: 4 })
+        ));
+
+        assert!(matches!(
+            TableFile::new(
+                "bn254",
+                TableForm::Evaluations,
+                None,
+                "",
+                vec![Fr::from(1u64); 3]
+            ),
+            Err(TableFileError::NotPow2 { len: 3 })
+        ));
+    }
+
+    #[test]
+    fn test_legacy_file() {
+        let t = gen_table::<Fr>(3, None).unwrap();
+        let legacy = serialize_vec(&t.coeffs);
+        let file = TableFile::<Fr>::from_bytes(&legacy, "bn254").unwrap();
+        assert_eq!(file.header.k, 3);
+        assert_eq!(file.header.form, TableForm::Coefficients);
+        assert_eq!(file.header.spec, LEGACY_SPEC);
+        assert_eq!(file.table().unwrap().coeffs[..t.coeffs.len()], t.coeffs[..]);
+    }
+}
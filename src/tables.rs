@@ -0,0 +1,253 @@
+use crate::error::{domain_of_log_size, SetupError};
+use crate::table::{gen_table, interpolate_table};
+use ark_ff::PrimeField;
+use ark_poly::{univariate::DensePolynomial, EvaluationDomain};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BitwiseOp {
+    Xor,
+    And,
+    Or,
+}
+
+impl BitwiseOp {
+    fn apply(&self, a: u64, b: u64) -> u64 {
+        match self {
+            Self::Xor => a ^ b,
+            Self::And => a & b,
+            Self::Or => a | b,
+        }
+    }
+}
+
+/// Describes how a table was generated, `to_string` and `parse` round trip.
+///
+/// Multi-value entries are packed into a single field element, e.g. 8-bit xor
+/// entries are `a + 2^8 b + 2^16 (a ^ b)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TableSpec {
+    /// `seeded:{k}:{seed}`, random table of `gen_table`
+    Seeded { k: usize, seed: String },
+    /// `sequence:{k}`, the range `[1..2^k]` of `gen_table`
+    Sequence { k: usize },
+    /// `range:{bits}`, the range `[0..2^bits)` used for range checks
+    Range { bits: usize },
+    /// `xor`, `and`, `or`, all pairs of bytes packed as `a + 2^8 b + 2^16 c`
+    Bitwise(BitwiseOp),
+    /// `spread:{bits}:{base}`, entries `x + 2^bits * sum_i x_i base^i` for every `bits` wide x.
+    /// Base 4 is the sparse form used for SHA-256, bigger bases are used for Keccak.
+    Spread { bits: usize, base: u64 },
+    /// `byte-to-bits`, entries `x + sum_i x_i 2^(8(i + 1))`, every bit of the byte in its own byte lane
+    ByteToBits,
+}
+
+impl TableSpec {
+    /// log2 of the table size
+    pub fn k(&self) -> usize {
+        match self {
+            Self::Seeded { k, .. } | Self::Sequence { k } => *k,
+            Self::Range { bits } | Self::Spread { bits, .. } => *bits,
+            Self::Bitwise(_) => 16,
+            Self::ByteToBits => 8,
+        }
+    }
+
+    /// Table entries, in order. Fails if the field has no subgroup of the table size.
+    pub fn evaluations<F: PrimeField>(&self) -> Result<Vec<F>, SetupError> {
+        let domain = domain_of_log_size::<F>(self.k())?;
+        let n = domain.size() as u64;
+        Ok(match self {
+            Self::Seeded { .. } => domain.fft(&self.table::<F>()?.coeffs),
+            Self::Sequence { .. } => (1..=n).map(F::from).collect(),
+            Self::Range { .. } => (0..n).map(F::from).collect(),
+            Self::Bitwise(op) => (0..n)
+                .map(|ab| {
+                    let (a, b) = (ab & 0xff, ab >> 8);
+                    F::from(ab + (op.apply(a, b) << 16))
+                })
+                .collect(),
+            Self::Spread { bits, base } => {
+                let shift = F::from(2u64).pow([*bits as u64]);
+                (0..n)
+                    .map(|x| F::from(x) + shift * spread::<F>(x, *bits, F::from(*base)))
+                    .collect()
+            }
+            Self::ByteToBits => {
+                let lane = F::from(256u64);
+                (0..n)
+                    .map(|x| F::from(x) + lane * spread::<F>(x, 8, lane))
+                    .collect()
+            }
+        })
+    }
+
+    /// Interpolated table, exactly what `gen_table` returns for seeded and sequence specs
+    pub fn table<F: PrimeField>(&self) -> Result<DensePolynomial<F>, SetupError> {
+        match self {
+            Self::Seeded { k, seed } => gen_table(*k, Some(seed)),
+            Self::Sequence { k } => gen_table(*k, None),
+            _ => {
+                let domain = domain_of_log_size::<F>(self.k())?;
+                interpolate_table(&domain, &self.evaluations::<F>()?)
+            }
+        }
+    }
+}
+
+/// sum_i x_i base^i over the low `bits` bits of x
+fn spread<F: PrimeField>(x: u64, bits: usize, base: F) -> F {
+    (0..bits)
+        .rev()
+        .fold(F::zero(), |acc, i| acc * base + F::from((x >> i) & 1))
+}
+
+impl fmt::Display for TableSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Seeded { k, seed } => write!(f, "seeded:{}:{}", k, seed),
+            Self::Sequence { k } => write!(f, "sequence:{}", k),
+            Self::Range { bits } => write!(f, "range:{}", bits),
+            Self::Bitwise(BitwiseOp::Xor) => write!(f, "xor"),
+            Self::Bitwise(BitwiseOp::And) => write!(f, "and"),
+            Self::Bitwise(BitwiseOp::Or) => write!(f, "or"),
+            Self::Spread { bits, base } => write!(f, "spread:{}:{}", bits, base),
+            Self::ByteToBits => write!(f, "byte-to-bits"),
+        }
+    }
+}
+
+impl FromStr for TableSpec {
+    type Err = String;
+
+    /// Accepts the names written by `to_string`, `spread:{bits}` defaults to base 4
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || format!("unknown table `{}`", s);
+        let number = |n: &str| n.parse::<u64>().map_err(|_| err());
+
+        let (name, args) = s.split_once(':').unwrap_or((s, ""));
+        let spec = match (name, args) {
+            ("seeded", args) => {
+                let (k, seed) = args.split_once(':').ok_or_else(err)?;
+                Self::Seeded {
+                    k: number(k)? as usize,
+                    seed: seed.to_string(),
+                }
+            }
+            ("sequence", k) => Self::Sequence {
+                k: number(k)? as usize,
+            },
+            ("range", bits) => Self::Range {
+                bits: number(bits)? as usize,
+            },
+            ("xor", "") => Self::Bitwise(BitwiseOp::Xor),
+            ("and", "") => Self::Bitwise(BitwiseOp::And),
+            ("or", "") => Self::Bitwise(BitwiseOp::Or),
+            ("spread", args) => {
+                let (bits, base) = args.split_once(':').unwrap_or((args, "4"));
+                Self::Spread {
+                    bits: number(bits)? as usize,
+                    base: number(base)?,
+                }
+            }
+            ("byte-to-bits", "") => Self::ByteToBits,
+            _ => return Err(err()),
+        };
+
+        match spec {
+            Self::Spread { base, .. } if base < 2 => Err(err()),
+            // entries are packed into u64 before they become field elements
+            Self::Range { bits } | Self::Spread { bits, .. } if bits > 32 => Err(err()),
+            spec => Ok(spec),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tables_test {
+    use super::{BitwiseOp, TableSpec};
+    use crate::gen_table;
+    use ark_bn254::Fr;
+
+    #[test]
+    fn test_spec_names_round_trip() {
+        let specs = [
+            TableSpec::Seeded {
+                k: 4,
+                seed: "a:b".to_string(),
+            },
+            TableSpec::Sequence { k: 4 },
+            TableSpec::Range { bits: 8 },
+            TableSpec::Bitwise(BitwiseOp::Xor),
+            TableSpec::Bitwise(BitwiseOp::And),
+            TableSpec::Bitwise(BitwiseOp::Or),
+            TableSpec::Spread { bits: 11, base: 4 },
+            TableSpec::ByteToBits,
+        ];
+        for spec in specs {
+            assert_eq!(spec.to_string().parse::<TableSpec>(), Ok(spec));
+        }
+
+        assert_eq!(
+            "spread:10".parse::<TableSpec>(),
+            Ok(TableSpec::Spread { bits: 10, base: 4 })
+        );
+        assert!("spread:10:1".parse::<TableSpec>().is_err());
+        assert!("range:x".parse::<TableSpec>().is_err());
+        assert!("xor:8".parse::<TableSpec>().is_err());
+    }
+
+    #[test]
+    fn test_tables() {
+        let range = TableSpec::Range { bits: 4 }.evaluations::<Fr>().unwrap();
+        assert_eq!(range.len(), 16);
+        assert_eq!(range[15], Fr::from(15u64));
+
+        let xor = TableSpec::Bitwise(BitwiseOp::Xor)
+            .evaluations::<Fr>()
+            .unwrap();
+        assert_eq!(xor.len(), 1 << 16);
+        // a = 0x0f, b = 0xf5
+        assert_eq!(xor[0xf50f], Fr::from(0x0f + (0xf5 << 8) + (0xfa << 16)));
+
+        let and = TableSpec::Bitwise(BitwiseOp::And)
+            .evaluations::<Fr>()
+            .unwrap();
+        assert_eq!(and[0xf50f], Fr::from(0x0f + (0xf5 << 8) + (0x05 << 16)));
+
+        let or = TableSpec::Bitwise(BitwiseOp::Or)
+            .evaluations::<Fr>()
+            .unwrap();
+        assert_eq!(or[0xf50f], Fr::from(0x0f + (0xf5 << 8) + (0xff << 16)));
+
+        // 0b101 spreads to 0b010001 in base 4
+        let spread = TableSpec::Spread { bits: 3, base: 4 }
+            .evaluations::<Fr>()
+            .unwrap();
+        assert_eq!(spread[0b101], Fr::from(0b101 + (0b010001 << 3)));
+
+        let bits = TableSpec::ByteToBits.evaluations::<Fr>().unwrap();
+        assert_eq!(bits.len(), 256);
+        assert_eq!(bits[0b11], Fr::from(0b11u64 + (1 << 8) + (1 << 16)));
+    }
+
+    #[test]
+    fn test_gen_table_specs() {
+        let seeded = TableSpec::Seeded {
+            k: 3,
+            seed: "seed".to_string(),
+        };
+        assert_eq!(
+            seeded.table::<Fr>().unwrap(),
+            gen_table::<Fr>(3, Some("seed")).unwrap()
+        );
+
+        let sequence = TableSpec::Sequence { k: 3 };
+        assert_eq!(
+            sequence.table::<Fr>().unwrap(),
+            gen_table::<Fr>(3, None).unwrap()
+        );
+        assert_eq!(sequence.evaluations::<Fr>().unwrap()[0], Fr::from(1u64));
+    }
+}
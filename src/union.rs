@@ -0,0 +1,241 @@
+use crate::compute_tau_powers;
+use crate::error::{check_tau, domain_of_log_size, SetupError};
+use crate::{compute_qs, interpolate_table};
+use ark_ec::pairing::Pairing;
+use ark_ff::{BigInteger, PrimeField};
+use ark_poly::{univariate::DensePolynomial, EvaluationDomain};
+use serde_json::json;
+
+/*
+    Several tables T_1, ..., T_m are concatenated, and the tag of the table every value came
+    from is folded into the value with a fixed shift, so the union is a single committed column:
+
+        value   t_1,0  t_1,1  ...  t_2,0  ...  t_m,l   t_m,l  ...
+        tag     1      1      ...  2      ...  m       m      ...
+        union   value + 2^TAG_SHIFT_BITS * tag
+
+    Values are below 2^TAG_SHIFT_BITS and tags below 2^64, so the folding is injective and the
+    union stays below the modulus of every supported curve. A lookup of f into T_j becomes a
+    lookup of f + 2^TAG_SHIFT_BITS * j into the union, and `compute_qs` runs once on it.
+    The union is padded by repeating its last row, which keeps the set of rows unchanged.
+*/
+
+/// Tags are shifted by 2^TAG_SHIFT_BITS before they are added to the values
+pub const TAG_SHIFT_BITS: u32 = 128;
+
+/// Position of a source table inside the union
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnionEntry {
+    pub name: String,
+    /// 1-based, so that a zero tag never matches
+    pub tag: u64,
+    pub offset: usize,
+    pub len: usize,
+}
+
+/// Several tables merged into one power of two table with the tag folded into every value
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TableUnion<F: PrimeField> {
+    pub k: usize,
+    pub entries: Vec<UnionEntry>,
+    pub values: Vec<F>,
+    pub tags: Vec<F>,
+}
+
+impl<F: PrimeField> TableUnion<F> {
+    /// Concatenates `(name, evaluations)` tables in order, every value has to be below
+    /// 2^TAG_SHIFT_BITS
+    pub fn new(tables: &[(String, Vec<F>)]) -> Result<Self, SetupError> {
+        if tables.iter().all(|(_, evals)| evals.is_empty()) {
+            return Err(SetupError::Empty);
+        }
+
+        let mut entries = vec![];
+        let mut values = vec![];
+        let mut tags = vec![];
+        for (j, (name, evals)) in tables.iter().enumerate() {
+            if let Some(index) = evals
+                .iter()
+                .position(|x| x.into_bigint().num_bits() > TAG_SHIFT_BITS)
+            {
+                return Err(SetupError::UnionValueTooLarge {
+                    name: name.clone(),
+                    index,
+                });
+            }
+            let tag = j as u64 + 1;
+            entries.push(UnionEntry {
+                name: name.clone(),
+                tag,
+                offset: values.len(),
+                len: evals.len(),
+            });
+            values.extend_from_slice(evals);
+            tags.resize(values.len(), F::from(tag));
+        }
+
+        let n = values.len().next_power_of_two();
+        let (last_value, last_tag) = (*values.last().unwrap(), *tags.last().unwrap());
+        values.resize(n, last_value);
+        tags.resize(n, last_tag);
+
+        Ok(Self {
+            k: n.trailing_zeros() as usize,
+            entries,
+            values,
+            tags,
+        })
+    }
+
+    pub fn entry(&self, name: &str) -> Option<&UnionEntry> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+
+    /// value + 2^TAG_SHIFT_BITS * tag of every row
+    pub fn evaluations(&self) -> Vec<F> {
+        let shift = tag_shift::<F>();
+        self.values
+            .iter()
+            .zip(self.tags.iter())
+            .map(|(value, tag)| *value + shift * tag)
+            .collect()
+    }
+
+    /// The folded column, interpolated over the 2^k subgroup
+    pub fn table(&self) -> Result<DensePolynomial<F>, SetupError> {
+        interpolate_table(&domain_of_log_size::<F>(self.k)?, &self.evaluations())
+    }
+
+    /// What a lookup of `value` into `entry` becomes in the union
+    pub fn lookup_value(&self, entry: &UnionEntry, value: F) -> F {
+        value + tag_shift::<F>() * F::from(entry.tag)
+    }
+
+    /// Machine readable offset map, for provers that translate lookups into the union
+    pub fn metadata_json(&self) -> String {
+        let tables: Vec<_> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                json!({
+                    "name": entry.name,
+                    "tag": entry.tag,
+                    "offset": entry.offset,
+                    "len": entry.len,
+                })
+            })
+            .collect();
+        let metadata = json!({
+            "k": self.k,
+            "tag_shift_bits": TAG_SHIFT_BITS,
+            "lookup": format!("value + 2^{} * tag", TAG_SHIFT_BITS),
+            "tables": tables,
+        });
+        serde_json::to_string_pretty(&metadata).unwrap()
+    }
+}
+
+fn tag_shift<F: PrimeField>() -> F {
+    F::from(2u64).pow([TAG_SHIFT_BITS as u64])
+}
+
+/// Quotients of the folded union, a single `compute_qs` run instead of one setup per table
+pub fn union_qs<E: Pairing>(
+    union: &TableUnion<E::ScalarField>,
+    tau: E::ScalarField,
+) -> Result<Vec<E::G1Affine>, SetupError> {
+    let domain = domain_of_log_size::<E::ScalarField>(union.k)?;
+    check_tau(tau, domain.size())?;
+    let tau_powers = compute_tau_powers(tau, domain.size());
+    compute_qs::<E>(&union.table()?, &domain, &tau_powers)
+}
+
+#[cfg(test)]
+mod union_test {
+    use super::{union_qs, TableUnion, TAG_SHIFT_BITS};
+    use crate::{compute_qs, compute_tau_powers, BitwiseOp, SetupError, TableSpec};
+    use ark_bn254::{Bn254, Fr};
+    use ark_ff::{Field, UniformRand};
+    use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+    use ark_std::test_rng;
+
+    #[test]
+    fn test_table_union() {
+        let range = TableSpec::Range { bits: 3 }.evaluations::<Fr>().unwrap();
+        let odd: Vec<Fr> = (0..5u64).map(|x| Fr::from(2 * x + 1)).collect();
+        let tables = vec![("range:3".to_string(), range), ("odd".to_string(), odd)];
+        let union = TableUnion::new(&tables).unwrap();
+
+        assert_eq!(union.k, 4);
+        let odd = union.entry("odd").unwrap();
+        assert_eq!((odd.tag, odd.offset, odd.len), (2, 8, 5));
+        assert_eq!(union.values[odd.offset + 2], Fr::from(5u64));
+        // padding repeats the last row
+        assert_eq!(union.values[15], Fr::from(9u64));
+        assert_eq!(union.tags[15], Fr::from(2u64));
+        let metadata: serde_json::Value = serde_json::from_str(&union.metadata_json()).unwrap();
+        assert_eq!(
+            metadata["tables"][1],
+            serde_json::json!({"name": "odd", "tag": 2, "offset": 8, "len": 5})
+        );
+        assert_eq!(metadata["tag_shift_bits"], TAG_SHIFT_BITS);
+
+        let domain = GeneralEvaluationDomain::<Fr>::new(16).unwrap();
+        let evals = domain.fft(&union.table().unwrap().coeffs);
+        assert_eq!(evals, union.evaluations());
+        // 7 is in the odd table but a lookup into it must not match the range table
+        let seven = union.lookup_value(odd, Fr::from(7u64));
+        assert_eq!(evals[odd.offset + 3], seven);
+        let range = union.entry("range:3").unwrap();
+        assert_ne!(evals[7], seven);
+        assert_eq!(evals[7], union.lookup_value(range, Fr::from(7u64)));
+
+        // names are escaped, including control characters
+        let name = "a\"b\\c\n\t\u{1}".to_string();
+        let union = TableUnion::new(&[(name.clone(), vec![Fr::from(1u64)])]).unwrap();
+        let metadata: serde_json::Value = serde_json::from_str(&union.metadata_json()).unwrap();
+        assert_eq!(metadata["tables"][0]["name"], name);
+
+        // values at or above the shift could collide with another tag
+        let large = Fr::from(2u64).pow([TAG_SHIFT_BITS as u64]);
+        assert!(matches!(
+            TableUnion::new(&[("large".to_string(), vec![Fr::from(1u64), large])]),
+            Err(SetupError::UnionValueTooLarge { index: 1, .. })
+        ));
+        assert!(matches!(
+            TableUnion::<Fr>::new(&[("empty".to_string(), vec![])]),
+            Err(SetupError::Empty)
+        ));
+    }
+
+    // cargo test --features=parallel test_union_qs
+    #[test]
+    fn test_union_qs() {
+        let tables = vec![
+            (
+                "xor".to_string(),
+                TableSpec::Bitwise(BitwiseOp::Xor)
+                    .evaluations::<Fr>()
+                    .unwrap()[..3]
+                    .to_vec(),
+            ),
+            (
+                "byte".to_string(),
+                TableSpec::Range { bits: 2 }.evaluations::<Fr>().unwrap(),
+            ),
+        ];
+        let union = TableUnion::new(&tables).unwrap();
+
+        let mut rng = test_rng();
+        let tau = Fr::rand(&mut rng);
+        let qs = union_qs::<Bn254>(&union, tau).unwrap();
+
+        let n = 1 << union.k;
+        let domain = GeneralEvaluationDomain::<Fr>::new(n).unwrap();
+        let tau_powers = compute_tau_powers(tau, n);
+        assert_eq!(
+            qs,
+            compute_qs::<Bn254>(&union.table().unwrap(), &domain, &tau_powers).unwrap()
+        );
+    }
+}
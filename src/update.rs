@@ -0,0 +1,301 @@
+use crate::common::evaluate_at_tau;
+use crate::compute_qs;
+use crate::error::{check_tau, domain, SetupError};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, Group, VariableBaseMSM};
+use ark_ff::{batch_inversion, Field, One, Zero};
+use ark_poly::{univariate::DensePolynomial, EvaluationDomain, GeneralEvaluationDomain};
+use ark_std::cfg_iter_mut;
+use std::collections::BTreeMap;
+use std::ops::Mul;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/*
+    Changing t_j by delta turns T(X) into T(X) + delta * L_j(X), and every
+
+               w^i     T(X) - t_i
+        Qi =  ----- * ------------
+                N       X - w^i
+
+    by a multiple of L_j(X) / (X - w^i) for i != j, and of (L_j(X) - 1) / (X - w^j) for i = j.
+
+    With tau known both are evaluated directly. With only [L_m]_1 available they are rewritten as
+
+        L_j(X) / (X - w^i) = (L_j(X) - w^(j - i) L_i(X)) / (w^j - w^i)
+
+        (L_j(X) - 1) / (X - w^j) = - sum_{m != j} L_m(X) / (X - w^j)
+*/
+
+/// Cached quotients of a table together with its commitments in both groups
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TableCommitments<E: Pairing> {
+    pub qs: Vec<E::G1Affine>,
+    /// [T(tau)]_1
+    pub t_1: E::G1Affine,
+    /// [T(tau)]_2
+    pub t_2: E::G2Affine,
+}
+
+impl<E: Pairing> TableCommitments<E> {
+    pub fn compute(
+        t: &DensePolynomial<E::ScalarField>,
+        domain: &GeneralEvaluationDomain<E::ScalarField>,
+        tau_powers: &[E::ScalarField],
+    ) -> Result<Self, SetupError> {
+        let qs = compute_qs::<E>(t, domain, tau_powers)?;
+        let t_at_tau = evaluate_at_tau(&t.coeffs, tau_powers);
+        Ok(Self {
+            qs,
+            t_1: E::G1::generator().mul(t_at_tau).into_affine(),
+            t_2: E::G2::generator().mul(t_at_tau).into_affine(),
+        })
+    }
+}
+
+/// Sums the deltas of repeated indices and drops the ones that cancel out
+fn merge_changes<F: Field>(
+    changes: &[(usize, F)],
+    n: usize,
+) -> Result<Vec<(usize, F)>, SetupError> {
+    let mut merged = BTreeMap::new();
+    for &(index, delta) in changes {
+        if index >= n {
+            return Err(SetupError::IndexOutOfRange { index, len: n });
+        }
+        *merged.entry(index).or_insert(F::zero()) += delta;
+    }
+    Ok(merged
+        .into_iter()
+        .filter(|(_, delta)| !delta.is_zero())
+        .collect())
+}
+
+/// Adds `delta_i` times G to every q_i
+fn add_scalars<G: CurveGroup>(points: &mut [G::Affine], scalars: &[G::ScalarField]) {
+    let gen = G::generator();
+    let mut updated: Vec<G> = points.iter().map(|p| p.into_group()).collect();
+    cfg_iter_mut!(updated)
+        .zip(scalars)
+        .for_each(|(p, s)| *p += gen.mul(s));
+    points.copy_from_slice(&G::normalize_batch(&updated));
+}
+
+/// Patches qs, [T]_1 and [T]_2 after table entries changed by `(index, delta)`,
+/// using tau directly. Costs N scalar multiplications however many entries change.
+pub fn update_qs<E: Pairing>(
+    commitments: &mut TableCommitments<E>,
+    changes: &[(usize, E::ScalarField)],
+    tau: E::ScalarField,
+) -> Result<(), SetupError> {
+    let n = commitments.qs.len();
+    let changes = merge_changes(changes, n)?;
+    let domain = domain::<E::ScalarField>(n)?;
+    check_tau(tau, n)?;
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    let n_inv = domain.size_as_field_element().inverse().unwrap();
+    let roots: Vec<E::ScalarField> = domain.elements().collect();
+
+    // 1 / (tau - w^i)
+    let mut tau_minus_roots_inv: Vec<E::ScalarField> = roots.iter().map(|w_i| tau - w_i).collect();
+    batch_inversion(&mut tau_minus_roots_inv);
+
+    // L_j(tau) = w^j / N * (tau^N - 1) / (tau - w^j)
+    let zh_at_tau = domain.evaluate_vanishing_polynomial(tau);
+    let lagrange_at_tau = |j: usize| roots[j] * n_inv * zh_at_tau * tau_minus_roots_inv[j];
+
+    let mut delta_table_at_tau = E::ScalarField::zero();
+    // sum_j delta_j L_j(tau), and the correction of q_j for the changed t_j itself
+    let mut own_entry = vec![E::ScalarField::zero(); n];
+    for &(j, delta) in changes.iter() {
+        delta_table_at_tau += delta * lagrange_at_tau(j);
+        own_entry[j] -= delta;
+    }
+
+    // w^i / N * (sum_j delta_j L_j(tau) - [i changed] delta_i) / (tau - w^i)
+    let scalars: Vec<E::ScalarField> = (0..n)
+        .map(|i| roots[i] * n_inv * (delta_table_at_tau + own_entry[i]) * tau_minus_roots_inv[i])
+        .collect();
+
+    add_scalars::<E::G1>(&mut commitments.qs, &scalars);
+    commitments.t_1 = (commitments.t_1 + E::G1::generator().mul(delta_table_at_tau)).into();
+    commitments.t_2 = (commitments.t_2 + E::G2::generator().mul(delta_table_at_tau)).into();
+    Ok(())
+}
+
+/// Patches qs, [T]_1 and [T]_2 after table entries changed by `(index, delta)`, without
+/// knowing tau. Needs the Lagrange commitments `[L_i(tau)]_1` and at least N G2 powers of tau.
+/// Costs N * (changes + 1) G1 and N * changes G2 scalar multiplications.
+pub fn update_qs_with_srs<E: Pairing>(
+    commitments: &mut TableCommitments<E>,
+    changes: &[(usize, E::ScalarField)],
+    lagrange_g1: &[E::G1Affine],
+    g2_powers: &[E::G2Affine],
+) -> Result<(), SetupError> {
+    let n = commitments.qs.len();
+    for found in [lagrange_g1.len(), g2_powers.len().min(n)] {
+        if found != n {
+            return Err(SetupError::SizeMismatch { expected: n, found });
+        }
+    }
+    let changes = merge_changes(changes, n)?;
+    let domain = domain::<E::ScalarField>(n)?;
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    let n_inv = domain.size_as_field_element().inverse().unwrap();
+    let roots: Vec<E::ScalarField> = domain.elements().collect();
+
+    // 1 / (w^j - w^i) for every changed j
+    let diffs_inv: Vec<Vec<E::ScalarField>> = changes
+        .iter()
+        .map(|&(j, _)| {
+            let mut diffs: Vec<E::ScalarField> = roots
+                .iter()
+                .enumerate()
+                .map(|(i, w_i)| {
+                    if i == j {
+                        E::ScalarField::one()
+                    } else {
+                        roots[j] - w_i
+                    }
+                })
+                .collect();
+            batch_inversion(&mut diffs);
+            diffs
+        })
+        .collect();
+
+    let mut updated: Vec<E::G1> = commitments.qs.iter().map(|q| q.into_group()).collect();
+
+    // i != j: delta/N * (w^i [L_j] - w^j [L_i]) / (w^j - w^i)
+    cfg_iter_mut!(updated).enumerate().for_each(|(i, q_i)| {
+        let mut l_i_coeff = E::ScalarField::zero();
+        for (&(j, delta), diffs_inv) in changes.iter().zip(diffs_inv.iter()) {
+            if i == j {
+                continue;
+            }
+            let c = delta * n_inv * diffs_inv[i];
+            *q_i += lagrange_g1[j].mul(c * roots[i]);
+            l_i_coeff -= c * roots[j];
+        }
+        *q_i += lagrange_g1[i].mul(l_i_coeff);
+    });
+
+    // i = j: -delta/N * sum_{m != j} (w^j [L_m] - w^m [L_j]) / (w^m - w^j)
+    for (&(j, delta), diffs_inv) in changes.iter().zip(diffs_inv.iter()) {
+        let c = delta * n_inv;
+        let mut scalars: Vec<E::ScalarField> = diffs_inv
+            .iter()
+            .map(|diff_inv| c * roots[j] * diff_inv)
+            .collect();
+        scalars[j] = -(0..n)
+            .filter(|&m| m != j)
+            .map(|m| c * roots[m] * diffs_inv[m])
+            .sum::<E::ScalarField>();
+        updated[j] += E::G1::msm_unchecked(lagrange_g1, &scalars);
+    }
+
+    commitments.qs = E::G1::normalize_batch(&updated);
+
+    // [L_j]_2 = 1/N * sum_k w^(-jk) [tau^k]_2
+    let mut t_1 = commitments.t_1.into_group();
+    let mut t_2 = commitments.t_2.into_group();
+    for &(j, delta) in changes.iter() {
+        t_1 += lagrange_g1[j].mul(delta);
+
+        let w_inv_j = roots[(n - j) % n];
+        let scalars: Vec<E::ScalarField> =
+            std::iter::successors(Some(delta * n_inv), |s| Some(*s * w_inv_j))
+                .take(n)
+                .collect();
+        t_2 += E::G2::msm_unchecked(&g2_powers[..n], &scalars);
+    }
+    commitments.t_1 = t_1.into_affine();
+    commitments.t_2 = t_2.into_affine();
+    Ok(())
+}
+
+#[cfg(test)]
+mod update_test {
+    use super::{update_qs, update_qs_with_srs, TableCommitments};
+    use crate::{
+        compute_g_powers, compute_tau_powers, gen_table, interpolate_table, lagrange_commitments,
+        SetupError,
+    };
+    use ark_bn254::{Bn254, Fr, G1Projective, G2Projective};
+    use ark_ff::UniformRand;
+    use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+    use ark_std::test_rng;
+
+    // cargo test --features=parallel test_update_qs
+    #[test]
+    fn test_update_qs() {
+        let k = 4;
+        let n = 1 << k;
+        let mut rng = test_rng();
+        let tau = Fr::rand(&mut rng);
+
+        let domain = GeneralEvaluationDomain::<Fr>::new(n).unwrap();
+        let tau_powers = compute_tau_powers(tau, n);
+        let lagrange_g1 = lagrange_commitments::<G1Projective>(tau, n as u64).unwrap();
+        let g2_powers = compute_g_powers::<G2Projective>(tau, n + 1);
+
+        let t = gen_table::<Fr>(k, Some("update")).unwrap();
+        let mut t_evals = domain.fft(&t.coeffs);
+        let commitments = TableCommitments::<Bn254>::compute(&t, &domain, &tau_powers).unwrap();
+
+        // index 3 changes twice, index 0 and the last index once
+        let changes = vec![
+            (3, Fr::rand(&mut rng)),
+            (0, Fr::rand(&mut rng)),
+            (n - 1, Fr::rand(&mut rng)),
+            (3, Fr::rand(&mut rng)),
+        ];
+        for &(index, delta) in changes.iter() {
+            t_evals[index] += delta;
+        }
+        let updated_t = interpolate_table(&domain, &t_evals).unwrap();
+        let expected =
+            TableCommitments::<Bn254>::compute(&updated_t, &domain, &tau_powers).unwrap();
+
+        let mut mock = commitments.clone();
+        update_qs(&mut mock, &changes, tau).unwrap();
+        assert_eq!(mock, expected);
+
+        let mut with_srs = commitments.clone();
+        update_qs_with_srs(&mut with_srs, &changes, &lagrange_g1, &g2_powers).unwrap();
+        assert_eq!(with_srs, expected);
+
+        let mut unchanged = commitments.clone();
+        update_qs(
+            &mut unchanged,
+            &[(2, Fr::from(1u64)), (2, -Fr::from(1u64))],
+            tau,
+        )
+        .unwrap();
+        assert_eq!(unchanged, commitments);
+
+        // out of range changes are rejected before anything is patched
+        assert!(matches!(
+            update_qs(
+                &mut unchanged,
+                &[(0, Fr::from(1u64)), (n, Fr::from(1u64))],
+                tau
+            ),
+            Err(SetupError::IndexOutOfRange { index: 16, len: 16 })
+        ));
+        assert!(matches!(
+            update_qs_with_srs(&mut unchanged, &changes, &lagrange_g1[1..], &g2_powers),
+            Err(SetupError::SizeMismatch {
+                expected: 16,
+                found: 15
+            })
+        ));
+        assert_eq!(unchanged, commitments);
+    }
+}
@@ -1,31 +1,26 @@
-use ark_ec::AffineRepr;
-use ark_ff::FftField;
-use ark_serialize::{CanonicalDeserialize, Read};
-use ark_std::log2;
+use crate::error::SetupError;
 use std::fs::File;
 
 pub fn is_pow_2(x: usize) -> bool {
-    (x & (x - 1)) == 0
+    x.is_power_of_two()
 }
 
+/// Smallest power of two that is at least `n`, 1 for `n = 0`
 pub fn next_pow2(n: usize) -> usize {
-    let two: u32 = 2;
-    let a: u32 = log2(n);
-
-    if two.pow(a - 1) == n as u32 {
-        return n;
-    }
-
-    two.pow(a).try_into().unwrap()
+    n.next_power_of_two()
 }
 
 #[cfg(feature = "parallel")]
 pub fn parallelize<T: Send, F: Fn(&mut [T], usize) + Send + Sync + Clone>(v: &mut [T], f: F) {
     let n = v.len();
+    // chunks_mut panics on a chunk size of 0
+    if n == 0 {
+        return;
+    }
     let num_threads = rayon::current_num_threads();
-    let mut chunk = (n as usize) / num_threads;
+    let mut chunk = n / num_threads;
     if chunk < num_threads {
-        chunk = n as usize;
+        chunk = n;
     }
 
     rayon::scope(|scope| {
@@ -39,24 +34,39 @@ pub fn parallelize<T: Send, F: Fn(&mut [T], usize) + Send + Sync + Clone>(v: &mu
     });
 }
 
-pub fn write_bytes(f_name: &str, data: &[u8]) {
-    use std::io::Write;
+/// Runs `f` once, or under several thread counts in parallel builds and checks that all
+/// runs agree, so that chunking in `parallelize` can never change the output.
+#[cfg(test)]
+pub(crate) fn run_with_threads<T: PartialEq + std::fmt::Debug + Send>(
+    f: impl Fn() -> T + Sync,
+) -> T {
+    #[cfg(not(feature = "parallel"))]
+    let res = f();
 
-    let mut file = File::create(f_name).unwrap();
-    file.write_all(&data).unwrap();
-}
+    #[cfg(feature = "parallel")]
+    let res = {
+        const THREAD_COUNTS: [usize; 5] = [1, 2, 3, 5, 8];
+        let mut results = THREAD_COUNTS.iter().map(|&num_threads| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()
+                .unwrap()
+                .install(&f)
+        });
+        let first = results.next().unwrap();
+        for (res, num_threads) in results.zip(THREAD_COUNTS.iter().skip(1)) {
+            assert_eq!(res, first, "output differs with {} threads", num_threads);
+        }
+        first
+    };
 
-use ark_serialize::CanonicalSerialize;
-pub fn serialize_vec<T: CanonicalSerialize>(x: &[T]) -> Vec<u8> {
-    let mut data = Vec::<u8>::new();
-    x.serialize_compressed(&mut data).unwrap();
-    data
+    res
 }
 
-pub fn deserialize_vec<T: CanonicalDeserialize>(path: &str) -> Vec<T> {
-    let mut file = File::open(path).expect("File could not be opened.");
-    let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer).unwrap();
+pub fn write_bytes(f_name: &str, data: &[u8]) -> Result<(), SetupError> {
+    use std::io::Write;
 
-    Vec::<T>::deserialize_compressed(buffer.as_slice()).unwrap()
+    let mut file = File::create(f_name)?;
+    file.write_all(data)?;
+    Ok(())
 }
@@ -0,0 +1,250 @@
+use crate::error::{domain, SetupError};
+use crate::setup_file::Section;
+use crate::vk::VerifierKey;
+use ark_ec::{pairing::Pairing, AffineRepr, VariableBaseMSM};
+use ark_ff::{Field, Zero};
+use ark_poly::{univariate::DensePolynomial, EvaluationDomain};
+
+/*
+    Every check folds all elements of a section with fresh random scalars r_i, so a single
+    wrong element makes the check fail except with probability 1/|F|:
+
+        powers        e(sum r_i [tau^(i+1)]_1, [1]_2) = e(sum r_i [tau^i]_1, [tau]_2)
+        lagrange      sum r_i [L_i]_1 = [sum r_i L_i(X)]_1, the coefficients are ifft(r)
+        openings      sum r_i [(L_i - L_i(0)) / X]_1 = [(sum r_i L_i(X) - sum r_i / N) / X]_1
+        qs            N w^-i q_i (X - w^i) = T(X) - t_i, so
+                      e(sum r_i N w^-i q_i, [tau]_2) = e(sum r_i (N q_i + [T]_1 - t_i [1]_1), [1]_2)
+*/
+
+fn random_scalars<F: Field>(n: usize) -> Vec<F> {
+    let mut rng = rand::thread_rng();
+    (0..n).map(|_| F::rand(&mut rng)).collect()
+}
+
+fn check(holds: bool, section: Section) -> Result<(), SetupError> {
+    if holds {
+        Ok(())
+    } else {
+        Err(SetupError::Inconsistent { section })
+    }
+}
+
+/// Checks that both vectors are consecutive powers of the same tau, starting at the generators
+pub fn verify_powers<E: Pairing>(
+    g1_powers: &[E::G1Affine],
+    g2_powers: &[E::G2Affine],
+) -> Result<(), SetupError> {
+    if g1_powers.len() < 2 || g2_powers.len() != g1_powers.len() + 1 {
+        return Err(SetupError::SizeMismatch {
+            expected: g1_powers.len().max(2) + 1,
+            found: g2_powers.len(),
+        });
+    }
+    check(g1_powers[0] == E::G1Affine::generator(), Section::G1Powers)?;
+    check(g2_powers[0] == E::G2Affine::generator(), Section::G2Powers)?;
+    if g2_powers[1].is_zero() || g2_powers[1] == g2_powers[0] {
+        return Err(SetupError::DegenerateTau);
+    }
+
+    let n = g1_powers.len();
+    let r = random_scalars::<E::ScalarField>(n - 1);
+    let lower = E::G1::msm_unchecked(&g1_powers[..n - 1], &r);
+    let upper = E::G1::msm_unchecked(&g1_powers[1..], &r);
+    let g1_holds = E::multi_pairing([upper, -lower], [g2_powers[0], g2_powers[1]]).is_zero();
+    check(g1_holds, Section::G1Powers)?;
+
+    let m = g2_powers.len();
+    let r = random_scalars::<E::ScalarField>(m - 1);
+    let lower = E::G2::msm_unchecked(&g2_powers[..m - 1], &r);
+    let upper = E::G2::msm_unchecked(&g2_powers[1..], &r);
+    let g2_holds = E::multi_pairing(
+        [g1_powers[0].into_group(), -g1_powers[1].into_group()],
+        [upper, lower],
+    )
+    .is_zero();
+    check(g2_holds, Section::G2Powers)
+}
+
+/// Checks `[L_i(tau)]_1` over the domain of size `lagrange.len()` against verified G1 powers
+pub fn verify_lagrange_commitments<E: Pairing>(
+    section: Section,
+    g1_powers: &[E::G1Affine],
+    lagrange: &[E::G1Affine],
+) -> Result<(), SetupError> {
+    let domain = domain::<E::ScalarField>(lagrange.len())?;
+    if g1_powers.len() < lagrange.len() {
+        return Err(SetupError::SizeMismatch {
+            expected: lagrange.len(),
+            found: g1_powers.len(),
+        });
+    }
+
+    let r = random_scalars::<E::ScalarField>(lagrange.len());
+    let coeffs = domain.ifft(&r);
+    let holds = E::G1::msm_unchecked(lagrange, &r)
+        == E::G1::msm_unchecked(&g1_powers[..lagrange.len()], &coeffs);
+    check(holds, section)
+}
+
+/// Checks `[(L_i(tau) - L_i(0)) / tau]_1` over the domain of size `openings.len()`
+pub fn verify_openings_at_zero<E: Pairing>(
+    g1_powers: &[E::G1Affine],
+    openings: &[E::G1Affine],
+) -> Result<(), SetupError> {
+    let domain = domain::<E::ScalarField>(openings.len())?;
+    if g1_powers.len() < openings.len() {
+        return Err(SetupError::SizeMismatch {
+            expected: openings.len(),
+            found: g1_powers.len(),
+        });
+    }
+
+    let r = random_scalars::<E::ScalarField>(openings.len());
+    let coeffs = domain.ifft(&r);
+    let holds = E::G1::msm_unchecked(openings, &r)
+        == E::G1::msm_unchecked(&g1_powers[..openings.len() - 1], &coeffs[1..]);
+    check(holds, Section::LagrangeOpeningsAtZero)
+}
+
+/// Checks the qs of table `t` over the domain of size `qs.len()`
+pub fn verify_qs<E: Pairing>(
+    g1_powers: &[E::G1Affine],
+    g2_powers: &[E::G2Affine],
+    t: &DensePolynomial<E::ScalarField>,
+    qs: &[E::G1Affine],
+) -> Result<(), SetupError> {
+    let domain = domain::<E::ScalarField>(qs.len())?;
+    let n = domain.size();
+    if t.coeffs.len() > n {
+        return Err(SetupError::SizeMismatch {
+            expected: n,
+            found: t.coeffs.len(),
+        });
+    }
+    if g1_powers.len() < n || g2_powers.len() < 2 {
+        return Err(SetupError::SizeMismatch {
+            expected: n,
+            found: g1_powers.len(),
+        });
+    }
+
+    let t_evals = domain.fft(&t.coeffs);
+    let t_1 = E::G1::msm_unchecked(&g1_powers[..t.coeffs.len()], &t.coeffs);
+    let n_field = domain.size_as_field_element();
+    let w_inv = domain.group_gen_inv();
+
+    let r = random_scalars::<E::ScalarField>(n);
+    let mut w_inv_pow_i = E::ScalarField::from(1u64);
+    let mut shifted = Vec::with_capacity(n);
+    for r_i in r.iter() {
+        shifted.push(*r_i * n_field * w_inv_pow_i);
+        w_inv_pow_i *= w_inv;
+    }
+    let scaled: Vec<E::ScalarField> = r.iter().map(|r_i| *r_i * n_field).collect();
+    let r_sum: E::ScalarField = r.iter().sum();
+    let r_t: E::ScalarField = r
+        .iter()
+        .zip(t_evals.iter())
+        .map(|(r_i, t_i)| *r_i * t_i)
+        .sum();
+
+    let lhs = E::G1::msm_unchecked(qs, &shifted);
+    let rhs = E::G1::msm_unchecked(qs, &scaled) + t_1 * r_sum - g1_powers[0] * r_t;
+    let holds = E::multi_pairing([lhs, -rhs], [g2_powers[1], g2_powers[0]]).is_zero();
+    check(holds, Section::Qs)
+}
+
+/// Checks that the verifier key matches verified G2 powers and table `t`
+pub fn verify_vk<E: Pairing>(
+    vk: &VerifierKey<E>,
+    g2_powers: &[E::G2Affine],
+    t: &DensePolynomial<E::ScalarField>,
+) -> Result<(), SetupError> {
+    let expected = VerifierKey::<E>::from_srs(
+        g2_powers,
+        t,
+        vk.table_n as usize,
+        vk.circuit_n as usize,
+        vk.table,
+    )?;
+    check(*vk == expected, Section::VerifierKey)
+}
+
+#[cfg(test)]
+mod verify_test {
+    use super::{
+        verify_lagrange_commitments, verify_openings_at_zero, verify_powers, verify_qs, verify_vk,
+    };
+    use crate::{gen_table, setup, Section, SetupError, TableFingerprint, VerifierKey};
+    use ark_bn254::{Bn254, Fr, G1Affine};
+    use ark_ec::{AffineRepr, CurveGroup};
+
+    fn corrupt(points: &[G1Affine], i: usize) -> Vec<G1Affine> {
+        let mut points = points.to_vec();
+        points[i] = (points[i] + G1Affine::generator()).into_affine();
+        points
+    }
+
+    fn is_inconsistent(result: Result<(), SetupError>, expected: Section) -> bool {
+        matches!(result, Err(SetupError::Inconsistent { section }) if section == expected)
+    }
+
+    // cargo test --features=parallel test_verify_setup
+    #[test]
+    fn test_verify_setup() {
+        let tau = Fr::from(100u64);
+        let (table_n, circuit_n) = (1 << 3, 1 << 4);
+        let t = gen_table::<Fr>(3, Some("verify")).unwrap();
+        let output = setup::<Bn254>(tau, table_n, circuit_n, &t).unwrap();
+        let (g1, g2) = (&output.g1_powers, &output.g2_powers);
+
+        verify_powers::<Bn254>(g1, g2).unwrap();
+        verify_lagrange_commitments::<Bn254>(
+            Section::LagrangeCommitments,
+            g1,
+            &output.lagrange_commitments,
+        )
+        .unwrap();
+        verify_lagrange_commitments::<Bn254>(
+            Section::CircuitLagrangeCommitments,
+            g1,
+            &output.circuit_lagrange_commitments,
+        )
+        .unwrap();
+        verify_openings_at_zero::<Bn254>(g1, &output.lagrange_openings_at_zero).unwrap();
+        verify_qs::<Bn254>(g1, g2, &t, &output.qs).unwrap();
+        let fingerprint = TableFingerprint::from_poly("bn254", &t, table_n).unwrap();
+        let vk = VerifierKey::<Bn254>::from_srs(g2, &t, table_n, circuit_n, fingerprint).unwrap();
+        verify_vk(&vk, g2, &t).unwrap();
+
+        assert!(is_inconsistent(
+            verify_powers::<Bn254>(&corrupt(g1, 5), g2),
+            Section::G1Powers
+        ));
+        assert!(is_inconsistent(
+            verify_lagrange_commitments::<Bn254>(
+                Section::LagrangeCommitments,
+                g1,
+                &corrupt(&output.lagrange_commitments, 2)
+            ),
+            Section::LagrangeCommitments
+        ));
+        assert!(is_inconsistent(
+            verify_openings_at_zero::<Bn254>(g1, &corrupt(&output.lagrange_openings_at_zero, 7)),
+            Section::LagrangeOpeningsAtZero
+        ));
+        assert!(is_inconsistent(
+            verify_qs::<Bn254>(g1, g2, &t, &corrupt(&output.qs, 0)),
+            Section::Qs
+        ));
+        let other = gen_table::<Fr>(3, Some("other")).unwrap();
+        assert!(is_inconsistent(
+            verify_qs::<Bn254>(g1, g2, &other, &output.qs),
+            Section::Qs
+        ));
+        assert!(is_inconsistent(
+            verify_vk(&vk, g2, &other),
+            Section::VerifierKey
+        ));
+    }
+}
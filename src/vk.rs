@@ -0,0 +1,133 @@
+use crate::error::SetupError;
+use crate::fingerprint::TableFingerprint;
+use crate::utils::is_pow_2;
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_poly::univariate::DensePolynomial;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+/// Everything the cq verifier needs for a table of size N and circuits of size n
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct VerifierKey<E: Pairing> {
+    pub table_n: u64,
+    pub circuit_n: u64,
+    /// table the key was derived for
+    pub table: TableFingerprint,
+    pub srs_g1_len: u64,
+    /// [1]_1
+    pub g1: E::G1Affine,
+    /// [1]_2
+    pub g2: E::G2Affine,
+    /// [tau]_2
+    pub tau_2: E::G2Affine,
+    /// [zv(tau)]_2 = [tau^N - 1]_2
+    pub zv_2: E::G2Affine,
+    /// [T(tau)]_2
+    pub t_2: E::G2Affine,
+    /// [tau^{max(N, n) - 1 - (n - 2)}]_2
+    pub x_b0_bound: E::G2Affine,
+}
+
+impl<E: Pairing> VerifierKey<E> {
+    /// Derives the key from the G2 powers alone, so it can be exported without tau.
+    /// `g2_powers` holds max(N, n) + 1 powers, as written by the setup.
+    pub fn from_srs(
+        g2_powers: &[E::G2Affine],
+        t: &DensePolynomial<E::ScalarField>,
+        table_n: usize,
+        circuit_n: usize,
+        table: TableFingerprint,
+    ) -> Result<Self, SetupError> {
+        for size in [table_n, circuit_n] {
+            if !is_pow_2(size) {
+                return Err(SetupError::NotPow2 { size });
+            }
+        }
+        // B0 has degree n - 2
+        if circuit_n < 2 {
+            return Err(SetupError::CircuitTooSmall { size: circuit_n });
+        }
+        let srs_g1_len = table_n.max(circuit_n);
+        if g2_powers.len() != srs_g1_len + 1 {
+            return Err(SetupError::SizeMismatch {
+                expected: srs_g1_len + 1,
+                found: g2_powers.len(),
+            });
+        }
+        if t.coeffs.len() > table_n {
+            return Err(SetupError::SizeMismatch {
+                expected: table_n,
+                found: t.coeffs.len(),
+            });
+        }
+
+        let g2 = g2_powers[0];
+        let t_2 = E::G2::msm_unchecked(&g2_powers[..t.coeffs.len()], &t.coeffs);
+        Ok(Self {
+            table_n: table_n as u64,
+            circuit_n: circuit_n as u64,
+            table,
+            srs_g1_len: srs_g1_len as u64,
+            g1: E::G1Affine::generator(),
+            g2,
+            tau_2: g2_powers[1],
+            zv_2: (g2_powers[table_n].into_group() - g2).into_affine(),
+            t_2: t_2.into_affine(),
+            x_b0_bound: g2_powers[srs_g1_len - 1 - (circuit_n - 2)],
+        })
+    }
+
+    /// Checks that the key was derived for the table with `fingerprint`
+    pub fn expect_table(&self, fingerprint: &TableFingerprint) -> Result<(), SetupError> {
+        if self.table != *fingerprint {
+            return Err(SetupError::TableMismatch {
+                expected: *fingerprint,
+                found: Some(self.table),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod vk_test {
+    use super::VerifierKey;
+    use crate::{compute_tau_powers, gen_table, setup, CommonPreprocessedInput, TableFingerprint};
+    use ark_bn254::{Bn254, Fr};
+    use ark_ec::{AffineRepr, CurveGroup};
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+    #[test]
+    fn test_vk_from_srs() {
+        let tau = Fr::from(100u64);
+        let (table_n, circuit_n) = (1 << 3, 1 << 4);
+        let t = gen_table::<Fr>(3, Some("vk")).unwrap();
+        let output = setup::<Bn254>(tau, table_n, circuit_n, &t).unwrap();
+
+        let fingerprint = TableFingerprint::from_poly("bn254", &t, table_n).unwrap();
+        let vk =
+            VerifierKey::<Bn254>::from_srs(&output.g2_powers, &t, table_n, circuit_n, fingerprint)
+                .unwrap();
+        vk.expect_table(&fingerprint).unwrap();
+        let cpi = CommonPreprocessedInput::<Bn254>::compute(
+            &compute_tau_powers(tau, circuit_n),
+            &t.coeffs,
+            table_n,
+            circuit_n,
+            circuit_n,
+        )
+        .unwrap();
+        assert_eq!(vk.zv_2, cpi.zv_2());
+        assert_eq!(vk.t_2, cpi.t_2());
+        assert_eq!(vk.x_b0_bound, cpi.x_b0_bound());
+        assert_eq!(vk.x_b0_bound, output.x_b0_bound);
+        assert_eq!(vk.tau_2, (vk.g2 * tau).into_affine());
+        assert_eq!(vk.g1, ark_bn254::G1Affine::generator());
+
+        let mut bytes = vec![];
+        vk.serialize_compressed(&mut bytes).unwrap();
+        assert_eq!(
+            VerifierKey::<Bn254>::deserialize_compressed(bytes.as_slice()).unwrap(),
+            vk
+        );
+    }
+}